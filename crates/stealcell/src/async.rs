@@ -0,0 +1,10 @@
+//! Cells that can be stolen from through shared references, awaiting the
+//! value while it's stolen. They work with any async runtime.
+
+mod async_steal_cell;
+#[cfg(feature = "futures")]
+mod cell_event;
+
+pub use async_steal_cell::*;
+#[cfg(feature = "futures")]
+pub use cell_event::*;
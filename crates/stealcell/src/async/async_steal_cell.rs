@@ -0,0 +1,848 @@
+use core::{
+	future::Future,
+	ops::{Deref, DerefMut},
+	panic::Location,
+	pin::Pin,
+	task::{Context, Poll, Waker},
+};
+use std::{
+	collections::VecDeque,
+	sync::{Mutex, MutexGuard, PoisonError},
+};
+
+#[cfg(feature = "futures")]
+use super::{CellEvent, cell_event::Subscribers};
+use crate::{ReturnError, StealCell, StealError, Stolen};
+
+/// A [StealCell] behind a mutex, so that it can be stolen from and returned
+/// to through a shared reference, where stealing waits for the value to be
+/// returned instead of panicking while it's stolen.
+///
+/// The lock is only held while stealing or returning, never while waiting.
+pub struct AsyncStealCell<T> {
+	shared: Mutex<Shared<T>>,
+}
+
+struct Shared<T> {
+	cell: StealCell<T>,
+	/// The futures waiting to steal the value, see [AsyncStealCell::steal].
+	stealers: Wakers,
+	/// The futures waiting for the value to be returned, see
+	/// [AsyncStealCell::wait_returned].
+	observers: Wakers,
+	/// How many times the value was returned, so that waiting for the next
+	/// return can't miss one that happens before the first poll.
+	returns: usize,
+	/// See [AsyncStealCell::events].
+	#[cfg(feature = "futures")]
+	subscribers: Subscribers,
+}
+
+/// Wakers of pending futures, longest waiting first, by key, so that
+/// futures can take theirs out when dropped.
+#[derive(Default)]
+struct Wakers {
+	next: usize,
+	wakers: VecDeque<(usize, Waker)>,
+}
+
+impl<T> AsyncStealCell<T> {
+	/// Creates a cell with the value in it.
+	pub fn new(value: T) -> Self {
+		Self::from(StealCell::new(value))
+	}
+
+	/// Creates a cell with a label, see [StealCell::new_named].
+	pub fn new_named(value: T, name: &'static str) -> Self {
+		Self::from(StealCell::new_named(value, name))
+	}
+
+	/// Creates a cell with nothing in it.
+	pub fn empty() -> Self {
+		Self::from(StealCell::empty())
+	}
+
+	/// A panic while the lock is held can't leave the cell in a broken
+	/// state, so the lock is taken even if poisoned.
+	fn lock(&self) -> MutexGuard<'_, Shared<T>> {
+		self.shared.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+
+	/// Steals the value once there is one in the cell, waiting for it to be
+	/// returned until then. Waits forever if the value is lost.
+	///
+	/// Panics if the cell is poisoned, see [StealError::Poisoned]!
+	#[track_caller]
+	pub fn steal(&self) -> Steal<'_, T> {
+		Steal {
+			cell: self,
+			key: None,
+			stolen_at: Location::caller(),
+		}
+	}
+
+	/// Steals the value once there is one in the cell, for the duration of
+	/// the future returned by `f`, returning it right after. The value is
+	/// also returned when that future fails, panics or is dropped before
+	/// completing, so cancelling the task can't lose it.
+	///
+	/// Panics if the cell is poisoned, or if the value can't be returned,
+	/// like when the cell was refilled in the meantime!
+	#[track_caller]
+	pub fn with_stolen<R>(&self, f: impl AsyncFnOnce(&mut T) -> R) -> impl Future<Output = R> {
+		let steal = self.steal_guard();
+		async move {
+			let mut stolen = steal.await;
+			f(stolen.get_mut()).await
+		}
+	}
+
+	/// Steals the value once there is one in the cell, like
+	/// [AsyncStealCell::steal], into a guard that returns it once dropped,
+	/// see [AsyncStolen].
+	///
+	/// Panics if the cell is poisoned, see [StealError::Poisoned]!
+	#[track_caller]
+	pub fn steal_guard(&self) -> impl Future<Output = AsyncStolen<'_, T>> {
+		let steal = self.steal();
+		async move {
+			AsyncStolen {
+				cell: self,
+				stolen: Some(steal.await),
+			}
+		}
+	}
+
+	/// Steals the value without waiting into a guard that returns it once
+	/// dropped, see [AsyncStealCell::steal_guard].
+	#[track_caller]
+	pub fn try_steal_guard(&self) -> Result<AsyncStolen<'_, T>, StealError> {
+		Ok(AsyncStolen {
+			cell: self,
+			stolen: Some(self.try_steal()?),
+		})
+	}
+
+	/// Waits for the value to be returned, without stealing it. Resolves
+	/// once the value is back in the cell, or right away if it's not stolen
+	/// when called. Waits forever if the value is lost.
+	pub fn wait_returned(&self) -> WaitReturned<'_, T> {
+		let shared = self.lock();
+		let returns = match shared.cell.is_stolen() {
+			true => Some(shared.returns),
+			false => None,
+		};
+		WaitReturned {
+			cell: self,
+			key: None,
+			returns,
+		}
+	}
+
+	/// Subscribes to the steals and returns of the value, from now on.
+	///
+	/// Each subscriber buffers up to 64 events, dropping the oldest ones
+	/// when it lags behind, so a subscriber that isn't polled often enough
+	/// skips events, but never holds up the cell.
+	#[cfg(feature = "futures")]
+	pub fn events(&self) -> Events<'_, T> {
+		Events {
+			cell: self,
+			key: self.lock().subscribers.subscribe(),
+		}
+	}
+
+	/// Steals the value without waiting, see [StealCell::try_steal].
+	#[must_use = "the stolen value must be returned with return_stolen or it will panic on drop"]
+	#[track_caller]
+	pub fn try_steal(&self) -> Result<Stolen<T>, StealError> {
+		let stolen_at = Location::caller();
+		let mut shared = self.lock();
+		let stolen = shared.cell.try_steal_at(stolen_at)?;
+		let wakers = shared.stolen(Some(stolen_at));
+		drop(shared);
+		wakers.into_iter().for_each(Waker::wake);
+		Ok(stolen)
+	}
+
+	/// Returns the stolen value, waking a future waiting to steal it, see
+	/// [StealCell::return_stolen].
+	///
+	/// Panics in the same cases as [StealCell::return_stolen]!
+	pub fn return_stolen(&self, stolen: Stolen<T>) {
+		let result = self.try_return_stolen(stolen);
+		if let Err(error) = result {
+			StealCell::<T>::fail_return(error);
+		}
+	}
+
+	/// Returns the stolen value, see [StealCell::try_return_stolen].
+	pub fn try_return_stolen(&self, stolen: Stolen<T>) -> Result<(), ReturnError<T>> {
+		let mut shared = self.lock();
+		shared.cell.try_return_stolen(stolen)?;
+		let wakers = shared.returned();
+		drop(shared);
+		wakers.into_iter().for_each(Waker::wake);
+		Ok(())
+	}
+
+	/// Returns `true` if the value is stolen.
+	pub fn is_stolen(&self) -> bool {
+		self.lock().cell.is_stolen()
+	}
+
+	/// Calls `f` with the cell while holding the lock, for the rest of its
+	/// API. Wakes a future waiting to steal the value if `f` leaves one in
+	/// the cell.
+	pub fn with_cell<R>(&self, f: impl FnOnce(&mut StealCell<T>) -> R) -> R {
+		let mut shared = self.lock();
+		let was_stolen = shared.cell.is_stolen();
+		let result = f(&mut shared.cell);
+		let wakers = match (was_stolen, shared.cell.is_stolen()) {
+			(true, false) => shared.returned(),
+			(false, false) => shared.stealers.pop().into_iter().collect(),
+			(false, true) => {
+				let (StealError::AlreadyStolen { stolen_at, .. }
+				| StealError::Poisoned { stolen_at, .. }) = shared.cell.already_stolen();
+				shared.stolen(stolen_at)
+			}
+			(true, true) => Vec::new(),
+		};
+		drop(shared);
+		wakers.into_iter().for_each(Waker::wake);
+		result
+	}
+
+	/// Hands over the cell.
+	pub fn into_cell(self) -> StealCell<T> {
+		self.shared
+			.into_inner()
+			.unwrap_or_else(PoisonError::into_inner)
+			.cell
+	}
+}
+
+impl<T> From<StealCell<T>> for AsyncStealCell<T> {
+	fn from(cell: StealCell<T>) -> Self {
+		Self {
+			shared: Mutex::new(Shared {
+				cell,
+				stealers: Wakers::default(),
+				observers: Wakers::default(),
+				returns: 0,
+				#[cfg(feature = "futures")]
+				subscribers: Subscribers::default(),
+			}),
+		}
+	}
+}
+
+impl<T> Default for AsyncStealCell<T>
+where
+	T: Default,
+{
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+impl<T> Shared<T> {
+	/// Counts a return of the value, taking out the wakers of every future
+	/// waiting for it, and of the longest waiting to steal it.
+	fn returned(&mut self) -> Vec<Waker> {
+		self.returns = self.returns.wrapping_add(1);
+		let mut wakers: Vec<_> = self.observers.drain().collect();
+		wakers.extend(self.stealers.pop());
+		#[cfg(feature = "futures")]
+		wakers.extend(self.subscribers.returned());
+		wakers
+	}
+
+	/// Publishes a steal of the value, taking out the wakers of the
+	/// subscribers to wake.
+	#[cfg_attr(not(feature = "futures"), expect(unused_variables))]
+	fn stolen(&mut self, at: Option<&'static Location<'static>>) -> Vec<Waker> {
+		#[cfg(feature = "futures")]
+		return self.subscribers.stolen(at);
+		#[cfg(not(feature = "futures"))]
+		Vec::new()
+	}
+}
+
+impl Wakers {
+	/// Registers the waker of the future with the key, giving it a key if
+	/// it has none yet, or if it was woken since.
+	fn register(&mut self, key: &mut Option<usize>, waker: &Waker) {
+		let registered = key.and_then(|key| self.wakers.iter_mut().find(|(k, _)| *k == key));
+		if let Some((_, registered)) = registered {
+			registered.clone_from(waker);
+			return;
+		}
+		*key = Some(self.next);
+		self.wakers.push_back((self.next, waker.clone()));
+		self.next = self.next.wrapping_add(1);
+	}
+
+	/// Takes out the waker of the future with the key, returning `false`
+	/// if it was woken already.
+	fn remove(&mut self, key: usize) -> bool {
+		let Some(index) = self.wakers.iter().position(|(k, _)| *k == key) else {
+			return false;
+		};
+		self.wakers.remove(index);
+		true
+	}
+
+	/// Takes out the waker of the longest waiting future.
+	fn pop(&mut self) -> Option<Waker> {
+		self.wakers.pop_front().map(|(_, waker)| waker)
+	}
+
+	/// Takes out the wakers of every future.
+	fn drain(&mut self) -> impl Iterator<Item = Waker> + '_ {
+		self.wakers.drain(..).map(|(_, waker)| waker)
+	}
+}
+
+/// Steals the value of an [AsyncStealCell] once it's returned, see
+/// [AsyncStealCell::steal].
+#[must_use = "futures do nothing unless polled"]
+pub struct Steal<'cell, T> {
+	cell: &'cell AsyncStealCell<T>,
+	/// The key of the registered waker, while waiting.
+	key: Option<usize>,
+	stolen_at: &'static Location<'static>,
+}
+
+impl<T> Future for Steal<'_, T> {
+	type Output = Stolen<T>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Stolen<T>> {
+		let this = &mut *self;
+		let mut shared = this.cell.lock();
+		match shared.cell.try_steal_at(this.stolen_at) {
+			Ok(stolen) => {
+				if let Some(key) = this.key.take() {
+					shared.stealers.remove(key);
+				}
+				let wakers = shared.stolen(Some(this.stolen_at));
+				drop(shared);
+				wakers.into_iter().for_each(Waker::wake);
+				Poll::Ready(stolen)
+			}
+			Err(error @ StealError::Poisoned { .. }) => {
+				if let Some(key) = this.key.take() {
+					shared.stealers.remove(key);
+				}
+				drop(shared);
+				StealCell::<T>::fail_steal(error)
+			}
+			Err(StealError::AlreadyStolen { .. }) => {
+				shared.stealers.register(&mut this.key, cx.waker());
+				Poll::Pending
+			}
+		}
+	}
+}
+
+impl<T> Drop for Steal<'_, T> {
+	fn drop(&mut self) {
+		let Some(key) = self.key else {
+			return;
+		};
+		let mut shared = self.cell.lock();
+		if shared.stealers.remove(key) || shared.cell.is_stolen() {
+			return;
+		}
+		// Woken for a value it won't steal anymore, the next one gets it.
+		let stealer = shared.stealers.pop();
+		drop(shared);
+		if let Some(stealer) = stealer {
+			stealer.wake();
+		}
+	}
+}
+
+/// A value stolen from an [AsyncStealCell] that is returned to it once
+/// dropped, see [AsyncStealCell::steal_guard].
+///
+/// Prefer it over [Stolen] for values held across an `.await` in a task
+/// that can be cancelled, as a [Stolen] dropped with the task is a lost
+/// value and panics, while this returns it. A [Stolen] is still needed to
+/// return the value by hand, or somewhere else, see
+/// [AsyncStolen::into_stolen].
+///
+/// Failing to return the value once dropped, like when the cell was
+/// refilled in the meantime, still panics, unless already panicking, in
+/// which case the value is lost like any other stolen value.
+#[must_use = "the value is returned right away if the guard is not used"]
+pub struct AsyncStolen<'cell, T> {
+	cell: &'cell AsyncStealCell<T>,
+	/// Only `None` once handed out.
+	stolen: Option<Stolen<T>>,
+}
+
+impl<T> AsyncStolen<'_, T> {
+	/// Returns a reference to the stolen value.
+	pub fn get(&self) -> &T {
+		// PANIC SAFETY: Only taken when the guard is consumed.
+		self.stolen.as_ref().unwrap().get()
+	}
+
+	/// Returns a mutable reference to the stolen value.
+	pub fn get_mut(&mut self) -> &mut T {
+		// PANIC SAFETY: Only taken when the guard is consumed.
+		self.stolen.as_mut().unwrap().get_mut()
+	}
+
+	/// Cancels returning the value, handing it out as a regular, armed
+	/// stolen value instead, to be returned by hand. The cell stays stolen.
+	pub fn into_stolen(mut self) -> Stolen<T> {
+		// PANIC SAFETY: Only taken when the guard is consumed.
+		self.stolen.take().unwrap()
+	}
+}
+
+impl<T> Deref for AsyncStolen<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		self.get()
+	}
+}
+
+impl<T> DerefMut for AsyncStolen<'_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.get_mut()
+	}
+}
+
+impl<T> Drop for AsyncStolen<'_, T> {
+	fn drop(&mut self) {
+		let Some(stolen) = self.stolen.take() else {
+			return;
+		};
+		if std::thread::panicking() {
+			let _ = self.cell.try_return_stolen(stolen);
+		} else {
+			self.cell.return_stolen(stolen);
+		}
+	}
+}
+
+/// Waits for the value of an [AsyncStealCell] to be returned, see
+/// [AsyncStealCell::wait_returned].
+#[must_use = "futures do nothing unless polled"]
+pub struct WaitReturned<'cell, T> {
+	cell: &'cell AsyncStealCell<T>,
+	/// The key of the registered waker, while waiting.
+	key: Option<usize>,
+	/// The count of returns when created, `None` if the value wasn't stolen.
+	returns: Option<usize>,
+}
+
+impl<T> Future for WaitReturned<'_, T> {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+		let this = &mut *self;
+		let mut shared = this.cell.lock();
+		let returned = this.returns.is_none_or(|returns| returns != shared.returns);
+		if returned || !shared.cell.is_stolen() {
+			if let Some(key) = this.key.take() {
+				shared.observers.remove(key);
+			}
+			return Poll::Ready(());
+		}
+		shared.observers.register(&mut this.key, cx.waker());
+		Poll::Pending
+	}
+}
+
+impl<T> Drop for WaitReturned<'_, T> {
+	fn drop(&mut self) {
+		if let Some(key) = self.key {
+			self.cell.lock().observers.remove(key);
+		}
+	}
+}
+
+/// The steals and returns of the value of an [AsyncStealCell], see
+/// [AsyncStealCell::events]. Never ends.
+#[cfg(feature = "futures")]
+#[must_use = "streams do nothing unless polled"]
+pub struct Events<'cell, T> {
+	cell: &'cell AsyncStealCell<T>,
+	key: usize,
+}
+
+#[cfg(feature = "futures")]
+impl<T> futures_core::Stream for Events<'_, T> {
+	type Item = CellEvent;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<CellEvent>> {
+		self.cell.lock().subscribers.poll_next(self.key, cx)
+	}
+}
+
+#[cfg(feature = "futures")]
+impl<T> Drop for Events<'_, T> {
+	fn drop(&mut self) {
+		self.cell.lock().subscribers.unsubscribe(self.key);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::{
+		Arc,
+		atomic::{AtomicUsize, Ordering},
+	};
+
+	use futures::{
+		executor::block_on,
+		task::{ArcWake, waker},
+	};
+
+	use super::*;
+
+	/// Counts the times it was woken.
+	#[derive(Default)]
+	struct Wakes(AtomicUsize);
+
+	impl ArcWake for Wakes {
+		fn wake_by_ref(arc_self: &Arc<Self>) {
+			arc_self.0.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	impl Wakes {
+		fn count(&self) -> usize {
+			self.0.load(Ordering::Relaxed)
+		}
+	}
+
+	fn poll<F: Future + Unpin>(future: &mut F, wakes: &Arc<Wakes>) -> Poll<F::Output> {
+		let waker = waker(Arc::clone(wakes));
+		Pin::new(future).poll(&mut Context::from_waker(&waker))
+	}
+
+	#[test]
+	fn steals_right_away_when_present() {
+		let cell = AsyncStealCell::new(1);
+		let (steal, line) = (cell.steal(), line!());
+		let mut stolen = block_on(steal);
+		assert!(cell.is_stolen());
+		let Err(StealError::AlreadyStolen { stolen_at, .. }) = cell.try_steal() else {
+			panic!("expected the value to be stolen");
+		};
+		let diagnostics = cfg!(any(debug_assertions, feature = "diagnostics"));
+		assert_eq!(stolen_at.map(|at| at.line()), diagnostics.then_some(line));
+		*stolen.get_mut() += 1;
+		cell.return_stolen(stolen);
+		assert_eq!(cell.into_cell().into_inner(), Some(2));
+	}
+
+	#[test]
+	fn wakes_a_stealer_on_return() {
+		let cell = AsyncStealCell::new(1);
+		let stolen = cell.steal_now();
+		let wakes = Arc::new(Wakes::default());
+		let mut steal = cell.steal();
+		assert!(poll(&mut steal, &wakes).is_pending());
+		assert!(poll(&mut steal, &wakes).is_pending());
+		assert_eq!(cell.lock().stealers.wakers.len(), 1);
+		assert_eq!(wakes.count(), 0);
+
+		cell.return_stolen(stolen);
+		assert_eq!(wakes.count(), 1);
+		let Poll::Ready(stolen) = poll(&mut steal, &wakes) else {
+			panic!("expected the value to be stolen");
+		};
+		assert!(cell.lock().stealers.wakers.is_empty());
+		cell.return_stolen(stolen);
+	}
+
+	/// Values can only be returned on the thread they were stolen on with
+	/// `debug_thread_affinity`.
+	#[test]
+	#[cfg(not(feature = "debug_thread_affinity"))]
+	fn wakes_a_stealer_on_another_thread() {
+		use std::{thread, time::Duration};
+
+		let cell = Arc::new(AsyncStealCell::new(vec![1]));
+		let stolen = cell.steal_now();
+		let stealer = thread::spawn({
+			let cell = Arc::clone(&cell);
+			move || block_on(cell.steal())
+		});
+		thread::sleep(Duration::from_millis(10));
+		cell.return_stolen(stolen);
+		let mut stolen = stealer.join().unwrap();
+		stolen.get_mut().push(2);
+		cell.return_stolen(stolen);
+		assert_eq!(cell.with_cell(|cell| cell.get().cloned()), Some(vec![1, 2]));
+	}
+
+	#[test]
+	fn dropped_stealers_clean_up() {
+		let cell = AsyncStealCell::new(1);
+		let stolen = cell.steal_now();
+		let wakes = Arc::new(Wakes::default());
+		let mut steal = cell.steal();
+		assert!(poll(&mut steal, &wakes).is_pending());
+		drop(steal);
+		assert!(cell.lock().stealers.wakers.is_empty());
+
+		cell.return_stolen(stolen);
+		assert_eq!(wakes.count(), 0);
+		cell.steal_now().discard();
+	}
+
+	#[test]
+	fn dropped_stealers_hand_over_their_wakeup() {
+		let cell = AsyncStealCell::new(1);
+		let stolen = cell.steal_now();
+		let (first_wakes, second_wakes) = (Arc::new(Wakes::default()), Arc::new(Wakes::default()));
+		let mut first = cell.steal();
+		let mut second = cell.steal();
+		assert!(poll(&mut first, &first_wakes).is_pending());
+		assert!(poll(&mut second, &second_wakes).is_pending());
+
+		cell.return_stolen(stolen);
+		assert_eq!((first_wakes.count(), second_wakes.count()), (1, 0));
+		drop(first);
+		assert_eq!(second_wakes.count(), 1);
+		let Poll::Ready(stolen) = poll(&mut second, &second_wakes) else {
+			panic!("expected the value to be stolen");
+		};
+		stolen.discard();
+	}
+
+	#[test]
+	fn waiting_for_a_present_value_resolves_right_away() {
+		let cell = AsyncStealCell::new(1);
+		block_on(cell.wait_returned());
+		let wakes = Arc::new(Wakes::default());
+		let mut waiting = cell.wait_returned();
+		let stolen = cell.steal_now();
+		assert!(poll(&mut waiting, &wakes).is_ready());
+		cell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn a_return_wakes_every_waiter() {
+		let cell = AsyncStealCell::new(1);
+		let stolen = cell.steal_now();
+		let wakes: Vec<_> = (0..3).map(|_| Arc::new(Wakes::default())).collect();
+		let mut waiting: Vec<_> = wakes.iter().map(|_| cell.wait_returned()).collect();
+		for (waiting, wakes) in waiting.iter_mut().zip(&wakes) {
+			assert!(poll(waiting, wakes).is_pending());
+		}
+		let mut stealing = cell.steal();
+		let stealing_wakes = Arc::new(Wakes::default());
+		assert!(poll(&mut stealing, &stealing_wakes).is_pending());
+
+		cell.return_stolen(stolen);
+		assert_eq!(stealing_wakes.count(), 1);
+		for (waiting, wakes) in waiting.iter_mut().zip(&wakes) {
+			assert_eq!(wakes.count(), 1);
+			assert!(poll(waiting, wakes).is_ready());
+		}
+		assert!(cell.lock().observers.wakers.is_empty());
+		drop(stealing);
+		assert!(!cell.is_stolen());
+	}
+
+	#[test]
+	fn waiting_does_not_miss_a_return_before_the_first_poll() {
+		let cell = AsyncStealCell::new(1);
+		let stolen = cell.steal_now();
+		let mut waiting = cell.wait_returned();
+		cell.return_stolen(stolen);
+		let stolen = cell.steal_now();
+
+		let wakes = Arc::new(Wakes::default());
+		assert!(poll(&mut waiting, &wakes).is_ready());
+		let mut waiting = cell.wait_returned();
+		assert!(poll(&mut waiting, &wakes).is_pending());
+		drop(waiting);
+		assert!(cell.lock().observers.wakers.is_empty());
+		cell.return_stolen(stolen);
+		assert_eq!(wakes.count(), 0);
+	}
+
+	#[test]
+	#[cfg(feature = "futures")]
+	fn subscribers_see_steals_and_returns_in_order() {
+		use futures::{FutureExt, StreamExt};
+
+		let cell = AsyncStealCell::new(1);
+		let mut early = cell.events();
+		let (stolen, line) = (cell.steal_now(), line!());
+		let mut late = cell.events();
+		cell.return_stolen(stolen);
+		let stolen = block_on(cell.steal());
+		cell.return_stolen(stolen);
+		cell.with_cell(|cell| cell.take());
+		cell.with_cell(|cell| cell.set(2).unwrap());
+
+		let next = |events: &mut Events<'_, usize>| events.next().now_or_never().flatten();
+		let Some(CellEvent::Stolen { at: Some(at) }) = next(&mut early) else {
+			panic!("expected the value to be stolen first");
+		};
+		assert_eq!(at.line(), line);
+		for events in [&mut early, &mut late] {
+			assert!(matches!(next(events), Some(CellEvent::Returned { .. })));
+			assert!(matches!(
+				next(events),
+				Some(CellEvent::Stolen { at: Some(_) })
+			));
+			assert!(matches!(next(events), Some(CellEvent::Returned { .. })));
+			assert_eq!(next(events), Some(CellEvent::Stolen { at: None }));
+			assert!(matches!(next(events), Some(CellEvent::Returned { .. })));
+			assert_eq!(next(events), None);
+		}
+	}
+
+	#[test]
+	#[cfg(feature = "futures")]
+	fn lagging_subscribers_skip_the_oldest_events() {
+		use futures::{FutureExt, StreamExt};
+
+		use crate::r#async::cell_event::BUFFERED_EVENTS;
+
+		let cell = AsyncStealCell::new(1);
+		let mut events = cell.events();
+		for _ in 0..BUFFERED_EVENTS {
+			cell.return_stolen(cell.steal_now());
+		}
+		let stolen = cell.steal_now();
+		let buffered: Vec<_> = (0..)
+			.map_while(|_| events.next().now_or_never().flatten())
+			.collect();
+		assert_eq!(buffered.len(), BUFFERED_EVENTS);
+		assert!(matches!(buffered[0], CellEvent::Returned { .. }));
+		assert!(matches!(buffered.last(), Some(CellEvent::Stolen { .. })));
+
+		let wakes = Arc::new(Wakes::default());
+		let waker = waker(Arc::clone(&wakes));
+		let mut context = Context::from_waker(&waker);
+		assert!(events.poll_next_unpin(&mut context).is_pending());
+		cell.return_stolen(stolen);
+		assert_eq!(wakes.count(), 1);
+		drop(events);
+		assert!(cell.lock().subscribers.subscribers.is_empty());
+	}
+
+	#[test]
+	fn returns_the_value_stolen_for_a_future() {
+		let cell = AsyncStealCell::new(1);
+		let doubled = block_on(cell.with_stolen(async |value| {
+			*value *= 2;
+			*value
+		}));
+		assert_eq!(doubled, 2);
+		let failed = block_on(cell.with_stolen(async |value| {
+			*value += 1;
+			Err::<(), _>("failed")
+		}));
+		assert_eq!(failed, Err("failed"));
+		assert_eq!(cell.into_cell().into_inner(), Some(3));
+	}
+
+	#[test]
+	fn returns_the_value_when_the_future_is_dropped_halfway() {
+		let cell = AsyncStealCell::new(1);
+		let wakes = Arc::new(Wakes::default());
+		let mut future = Box::pin(cell.with_stolen(async |value| {
+			*value += 1;
+			futures::future::pending::<()>().await;
+			*value += 1;
+		}));
+		assert!(poll(&mut future, &wakes).is_pending());
+		assert!(cell.is_stolen());
+		drop(future);
+		assert!(!cell.is_stolen());
+		assert_eq!(cell.into_cell().into_inner(), Some(2));
+	}
+
+	#[test]
+	fn returns_the_value_when_the_future_panics() {
+		let cell = AsyncStealCell::new(1);
+		let hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(|_| {}));
+		let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+			block_on(cell.with_stolen(async |value| {
+				*value += 1;
+				panic!("the request failed");
+			}))
+		}));
+		std::panic::set_hook(hook);
+		assert!(result.is_err());
+		assert!(!cell.is_stolen());
+		assert_eq!(cell.into_cell().into_inner(), Some(2));
+	}
+
+	#[test]
+	fn cancelling_a_task_returns_its_guarded_value() {
+		use futures::{
+			executor::LocalPool,
+			future::{AbortHandle, Abortable},
+			task::LocalSpawnExt,
+		};
+
+		let cell = Arc::new(AsyncStealCell::new(1));
+		let mut pool = LocalPool::new();
+		let (abort, registration) = AbortHandle::new_pair();
+		let task = Abortable::new(
+			{
+				let cell = Arc::clone(&cell);
+				async move {
+					let mut stolen = cell.steal_guard().await;
+					*stolen += 1;
+					futures::future::pending::<()>().await;
+				}
+			},
+			registration,
+		);
+		let task = pool.spawner().spawn_local_with_handle(task).unwrap();
+		pool.run_until_stalled();
+		assert!(cell.is_stolen());
+
+		abort.abort();
+		assert!(pool.run_until(task).is_err());
+		assert!(!cell.is_stolen());
+		assert_eq!(cell.with_cell(|cell| cell.get().copied()), Some(2));
+	}
+
+	#[test]
+	fn guards_hand_out_values_to_return_by_hand() {
+		let cell = AsyncStealCell::new(1);
+		let mut guard = cell.try_steal_guard().unwrap();
+		*guard.get_mut() += 1;
+		let stolen = guard.into_stolen();
+		assert!(cell.is_stolen());
+		cell.return_stolen(stolen);
+		drop(block_on(cell.steal_guard()));
+		assert_eq!(cell.into_cell().into_inner(), Some(2));
+	}
+
+	#[test]
+	#[should_panic(expected = "trying to return a stolen value, but this cell is not empty!")]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn guards_panic_when_the_value_can_not_be_returned() {
+		let cell = AsyncStealCell::new(1);
+		let guard = cell.try_steal_guard().unwrap();
+		cell.with_cell(|cell| cell.set(2).unwrap());
+		drop(guard);
+	}
+
+	impl<T> AsyncStealCell<T> {
+		#[track_caller]
+		fn steal_now(&self) -> Stolen<T> {
+			self.try_steal().unwrap()
+		}
+	}
+}
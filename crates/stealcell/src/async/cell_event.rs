@@ -0,0 +1,99 @@
+use core::{
+	panic::Location,
+	task::{Context, Poll, Waker},
+	time::Duration,
+};
+use std::{collections::VecDeque, time::Instant, vec::Vec};
+
+/// How many events each subscriber buffers, see
+/// [AsyncStealCell::events](super::AsyncStealCell::events).
+pub(super) const BUFFERED_EVENTS: usize = 64;
+
+/// A steal or a return of the value of an
+/// [AsyncStealCell](super::AsyncStealCell), see
+/// [AsyncStealCell::events](super::AsyncStealCell::events).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellEvent {
+	/// The value was stolen.
+	Stolen {
+		/// Where it was stolen, `None` if it was taken instead.
+		at: Option<&'static Location<'static>>,
+	},
+	/// The value was returned, or the empty cell refilled.
+	Returned {
+		/// How long it was out of the cell, zero if it was never in it.
+		held_for: Duration,
+	},
+}
+
+/// The events buffered for each subscriber, by key.
+#[derive(Default)]
+pub(super) struct Subscribers {
+	next: usize,
+	pub(super) subscribers: Vec<(usize, Subscriber)>,
+	/// When the value was last stolen.
+	stolen_since: Option<Instant>,
+}
+
+#[derive(Default)]
+pub(super) struct Subscriber {
+	events: VecDeque<CellEvent>,
+	waker: Option<Waker>,
+}
+
+impl Subscribers {
+	pub(super) fn subscribe(&mut self) -> usize {
+		let key = self.next;
+		self.next = self.next.wrapping_add(1);
+		self.subscribers.push((key, Subscriber::default()));
+		key
+	}
+
+	pub(super) fn unsubscribe(&mut self, key: usize) {
+		self.subscribers.retain(|(k, _)| *k != key);
+	}
+
+	/// Publishes a steal, taking out the wakers of the subscribers.
+	pub(super) fn stolen(&mut self, at: Option<&'static Location<'static>>) -> Vec<Waker> {
+		self.stolen_since = Some(Instant::now());
+		self.publish(CellEvent::Stolen { at })
+	}
+
+	/// Publishes a return, taking out the wakers of the subscribers.
+	pub(super) fn returned(&mut self) -> Vec<Waker> {
+		let held_for = self
+			.stolen_since
+			.take()
+			.map_or(Duration::ZERO, |stolen_since| stolen_since.elapsed());
+		self.publish(CellEvent::Returned { held_for })
+	}
+
+	/// Buffers the event for every subscriber, dropping their oldest one if
+	/// they already have [BUFFERED_EVENTS].
+	fn publish(&mut self, event: CellEvent) -> Vec<Waker> {
+		let mut wakers = Vec::new();
+		for (_, subscriber) in &mut self.subscribers {
+			if subscriber.events.len() == BUFFERED_EVENTS {
+				subscriber.events.pop_front();
+			}
+			subscriber.events.push_back(event);
+			wakers.extend(subscriber.waker.take());
+		}
+		wakers
+	}
+
+	pub(super) fn poll_next(
+		&mut self,
+		key: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Option<CellEvent>> {
+		let Some((_, subscriber)) = self.subscribers.iter_mut().find(|(k, _)| *k == key) else {
+			return Poll::Ready(None);
+		};
+		if let Some(event) = subscriber.events.pop_front() {
+			return Poll::Ready(Some(event));
+		}
+		subscriber.waker = Some(cx.waker().clone());
+		Poll::Pending
+	}
+}
@@ -0,0 +1,192 @@
+use core::{
+	fmt::{self, Debug, Formatter},
+	marker::PhantomData,
+	ops::{Deref, DerefMut},
+};
+
+use crate::{ReturnError, StealCell, StealError, Stolen};
+
+/// Ties values to the one call of [StealCell::with_brand] they were made
+/// in. Invariant, so that brands of different calls never unify.
+type Brand<'brand> = PhantomData<fn(&'brand ()) -> &'brand ()>;
+
+/// A cell borrowed by [StealCell::with_brand], whose stolen values can only
+/// be returned to it, which the compiler checks instead of a panic.
+///
+/// It derefs to the cell for the rest of the API, with `steal`, `try_steal`,
+/// `return_stolen` and `try_return_stolen` taking and handing out
+/// [BrandedStolen] values instead.
+pub struct BrandedCell<'brand, 'cell, T> {
+	cell: &'cell mut StealCell<T>,
+	brand: Brand<'brand>,
+}
+
+/// A value stolen from a [BrandedCell], which can only be returned to it.
+/// Like [Stolen], it panics if dropped without returning it!
+#[must_use = "a stolen value must be returned with return_stolen or it will panic on drop"]
+pub struct BrandedStolen<'brand, T> {
+	stolen: Stolen<T>,
+	brand: Brand<'brand>,
+}
+
+impl<T> StealCell<T> {
+	/// Calls `f` with the cell branded with a lifetime of its own, so that
+	/// returning a value stolen from a different branded cell to it doesn't
+	/// compile. Neither the cell nor its stolen values can leave `f`.
+	pub fn with_brand<R>(
+		&mut self,
+		f: impl for<'brand> FnOnce(BrandedCell<'brand, '_, T>) -> R,
+	) -> R {
+		f(BrandedCell {
+			cell: self,
+			brand: PhantomData,
+		})
+	}
+}
+
+impl<'brand, T> BrandedCell<'brand, '_, T> {
+	/// Steals the value, see [StealCell::steal].
+	#[track_caller]
+	pub fn steal(&mut self) -> BrandedStolen<'brand, T> {
+		BrandedStolen {
+			stolen: self.cell.steal(),
+			brand: PhantomData,
+		}
+	}
+
+	/// Steals the value, see [StealCell::try_steal].
+	#[must_use = "the stolen value must be returned with return_stolen or it will panic on drop"]
+	#[track_caller]
+	pub fn try_steal(&mut self) -> Result<BrandedStolen<'brand, T>, StealError> {
+		let stolen = self.cell.try_steal()?;
+		Ok(BrandedStolen {
+			stolen,
+			brand: PhantomData,
+		})
+	}
+
+	/// Returns the stolen value, see [StealCell::return_stolen]. It still
+	/// panics if the cell was refilled since, or if it's occupied!
+	pub fn return_stolen(&mut self, stolen: BrandedStolen<'brand, T>) {
+		self.cell.return_stolen(stolen.stolen);
+	}
+
+	/// Returns the stolen value, see [StealCell::try_return_stolen]. A value
+	/// handed back in the error is a plain stolen value.
+	pub fn try_return_stolen(
+		&mut self,
+		stolen: BrandedStolen<'brand, T>,
+	) -> Result<(), ReturnError<T>> {
+		self.cell.try_return_stolen(stolen.stolen)
+	}
+}
+
+impl<T> Deref for BrandedCell<'_, '_, T> {
+	type Target = StealCell<T>;
+
+	fn deref(&self) -> &Self::Target {
+		self.cell
+	}
+}
+
+impl<T> DerefMut for BrandedCell<'_, '_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.cell
+	}
+}
+
+impl<T> BrandedStolen<'_, T> {
+	/// Returns a reference to the stolen value, see [Stolen::get].
+	pub fn get(&self) -> &T {
+		self.stolen.get()
+	}
+
+	/// Returns a mutable reference to the stolen value, see
+	/// [Stolen::get_mut].
+	pub fn get_mut(&mut self) -> &mut T {
+		self.stolen.get_mut()
+	}
+
+	/// Hands over the plain stolen value, which is checked at runtime from
+	/// then on, like any other.
+	pub fn into_stolen(self) -> Stolen<T> {
+		self.stolen
+	}
+}
+
+/// Prints like the plain [Stolen] value.
+impl<T> Debug for BrandedStolen<'_, T>
+where
+	T: Debug,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		self.stolen.fmt(f)
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> Deref for BrandedStolen<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		self.get()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> DerefMut for BrandedStolen<'_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.get_mut()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{StealCell, StealError};
+
+	#[test]
+	fn steals_and_returns() {
+		let mut stealcell = StealCell::new(1);
+		let value = stealcell.with_brand(|mut cell| {
+			let mut stolen = cell.steal();
+			assert!(cell.is_stolen());
+			assert!(matches!(
+				cell.try_steal(),
+				Err(StealError::AlreadyStolen { .. })
+			));
+			*stolen.get_mut() += 1;
+			cell.return_stolen(stolen);
+
+			let stolen = cell.try_steal().unwrap();
+			assert!(cell.try_return_stolen(stolen).is_ok());
+			*cell.get().unwrap()
+		});
+		assert_eq!(value, 2);
+		assert!(!stealcell.is_stolen());
+	}
+
+	#[test]
+	fn nests_cells_of_their_own_brands() {
+		let mut a = StealCell::new(1);
+		let mut b = StealCell::new(2);
+		a.with_brand(|mut a| {
+			b.with_brand(|mut b| {
+				let mut stolen_a = a.steal();
+				let stolen_b = b.steal();
+				*stolen_a.get_mut() += *stolen_b.get();
+				b.return_stolen(stolen_b);
+				a.return_stolen(stolen_a);
+			});
+		});
+		assert_eq!(a, 3);
+		assert_eq!(b, 2);
+	}
+
+	#[test]
+	fn composes_with_plain_stolen_values() {
+		let mut stealcell = StealCell::new(1);
+		let stolen = stealcell.with_brand(|mut cell| cell.steal().into_stolen());
+		stealcell.return_stolen(stolen);
+		assert!(!stealcell.is_stolen());
+	}
+}
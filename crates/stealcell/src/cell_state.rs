@@ -0,0 +1,24 @@
+use core::fmt::{self, Display, Formatter};
+
+/// The state a [StealCell](crate::StealCell) is in, see
+/// [StealCell::status](crate::StealCell::status).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellState {
+	/// The value is in the cell.
+	Present,
+	/// The value was stolen from the cell.
+	Stolen,
+	/// The value stolen from the cell was lost without being returned. Only
+	/// tracked with the `poison` feature.
+	Poisoned,
+}
+
+impl Display for CellState {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Present => f.write_str("present"),
+			Self::Stolen => f.write_str("stolen"),
+			Self::Poisoned => f.write_str("poisoned"),
+		}
+	}
+}
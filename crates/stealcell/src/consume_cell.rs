@@ -0,0 +1,184 @@
+use core::{
+	fmt::{self, Debug, Formatter},
+	panic::Location,
+};
+
+use crate::{ShortTypeName, value_type_name, violation::misuse};
+#[cfg(any(feature = "slim_panics", feature = "typed_panics"))]
+use crate::{StealViolation, ViolationKind};
+
+/// What a consumed cell prints instead of its value.
+const CONSUMED_MARKER: &str = "<consumed>";
+
+/// A cell whose value is taken exactly once and never comes back, so there
+/// is nothing to return and nothing to panic on drop. It remembers where it
+/// was consumed, to report it when consumed again.
+pub struct ConsumeCell<T> {
+	value: Option<T>,
+	/// Where the value was consumed.
+	consumed_at: Option<&'static Location<'static>>,
+	/// Included in panic messages, see [ConsumeCell::new_named].
+	label: Option<&'static str>,
+}
+
+impl<T> ConsumeCell<T> {
+	/// Creates a cell with the value in it.
+	pub fn new(value: T) -> Self {
+		Self {
+			value: Some(value),
+			consumed_at: None,
+			label: None,
+		}
+	}
+
+	/// Creates a cell with a label that is included in the panic message
+	/// when it's consumed again.
+	pub fn new_named(value: T, name: &'static str) -> Self {
+		Self {
+			label: Some(name),
+			..Self::new(value)
+		}
+	}
+
+	/// Returns the label of the cell, if it has one.
+	pub fn label(&self) -> Option<&'static str> {
+		self.label
+	}
+
+	/// Takes the value out for good.
+	///
+	/// Panics if already consumed, reporting where it was consumed first!
+	#[track_caller]
+	pub fn consume(&mut self) -> T {
+		let consumed_at = self.consumed_at;
+		self.try_consume().unwrap_or_else(|| {
+			misuse!(
+				StealViolation::new(
+					ViolationKind::AlreadyStolen,
+					value_type_name::<T>(),
+					self.label,
+					consumed_at,
+				),
+				"{}",
+				AlreadyConsumed {
+					type_name: value_type_name::<T>(),
+					label: self.label,
+					consumed_at,
+				}
+			)
+		})
+	}
+
+	/// Like [ConsumeCell::consume] but returns `None` if already consumed.
+	#[track_caller]
+	pub fn try_consume(&mut self) -> Option<T> {
+		let value = self.value.take()?;
+		self.consumed_at = Some(Location::caller());
+		Some(value)
+	}
+
+	/// Returns `true` if the value was consumed.
+	pub fn is_consumed(&self) -> bool {
+		self.value.is_none()
+	}
+
+	/// Returns where the value was consumed, if it was.
+	pub fn consumed_at(&self) -> Option<&'static Location<'static>> {
+		self.consumed_at
+	}
+}
+
+/// Prints `ConsumeCell(<value>)`, or `ConsumeCell(<consumed>)` once
+/// consumed.
+impl<T> Debug for ConsumeCell<T>
+where
+	T: Debug,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let consumed = format_args!("{CONSUMED_MARKER}");
+		let value: &dyn Debug = match &self.value {
+			Some(value) => value,
+			None => &consumed,
+		};
+		f.debug_tuple("ConsumeCell").field(value).finish()
+	}
+}
+
+impl<T> From<T> for ConsumeCell<T> {
+	fn from(value: T) -> Self {
+		Self::new(value)
+	}
+}
+
+/// The message of consuming a cell again.
+struct AlreadyConsumed {
+	type_name: &'static str,
+	label: Option<&'static str>,
+	consumed_at: Option<&'static Location<'static>>,
+}
+
+impl fmt::Display for AlreadyConsumed {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let type_name = ShortTypeName(self.type_name);
+		match self.label {
+			Some(label) => write!(f, "value already consumed from '{label}': {type_name}")?,
+			None => write!(f, "value already consumed: {type_name}")?,
+		}
+		if let Some(consumed_at) = self.consumed_at {
+			write!(f, ", first consumed at {consumed_at}")?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	fn panic_message(fun: impl FnOnce()) -> String {
+		let hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(|_| {}));
+		let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(fun))
+			.expect_err("expected a panic");
+		std::panic::set_hook(hook);
+		payload.downcast_ref::<String>().cloned().unwrap()
+	}
+
+	#[test]
+	fn consumes_once() {
+		let mut config = ConsumeCell::new(vec![1, 2]);
+		assert!(!config.is_consumed());
+		assert_eq!(format!("{config:?}"), "ConsumeCell([1, 2])");
+		let (value, line) = (config.consume(), line!());
+		assert_eq!(value, [1, 2]);
+		assert!(config.is_consumed());
+		assert_eq!(config.consumed_at().map(Location::line), Some(line));
+		assert_eq!(config.try_consume(), None);
+		assert_eq!(config.consumed_at().map(Location::line), Some(line));
+		assert_eq!(format!("{config:?}"), "ConsumeCell(<consumed>)");
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	fn reports_where_it_was_first_consumed() {
+		let mut token = ConsumeCell::from(1_u8);
+		let line = line!() + 1;
+		let _ = token.try_consume();
+		let message = panic_message(|| {
+			token.consume();
+		});
+		assert!(message.starts_with(&format!(
+			"value already consumed: u8, first consumed at {}:{line}:",
+			file!()
+		)));
+
+		let mut token = ConsumeCell::new_named(1_u8, "wakeup");
+		assert_eq!(token.label(), Some("wakeup"));
+		token.consume();
+		let message = panic_message(|| {
+			token.consume();
+		});
+		assert!(message.starts_with("value already consumed from 'wakeup': u8, first consumed at"));
+	}
+}
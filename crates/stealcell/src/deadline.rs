@@ -0,0 +1,144 @@
+use std::{
+	panic::Location,
+	sync::{PoisonError, RwLock},
+	time::Duration,
+};
+
+use crate::ShortTypeName;
+
+/// Handed to the handler installed with [set_deadline_handler] when a value
+/// stays stolen for longer than the deadline of its cell, see
+/// [StealCell::set_steal_deadline](crate::StealCell::set_steal_deadline).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverdueSteal {
+	/// How long the value was out, or has been out so far.
+	pub elapsed: Duration,
+	/// The deadline of the cell.
+	pub deadline: Duration,
+	/// The type name of the stolen value.
+	pub type_name: &'static str,
+	/// The label of the cell, see
+	/// [StealCell::new_named](crate::StealCell::new_named).
+	pub label: Option<&'static str>,
+	/// Where the value was stolen.
+	pub stolen_at: Option<&'static Location<'static>>,
+}
+
+impl core::fmt::Display for OverdueSteal {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		write!(
+			f,
+			"value stolen for {:?}, longer than its deadline of {:?}! {}",
+			self.elapsed,
+			self.deadline,
+			ShortTypeName(self.type_name)
+		)?;
+		if let Some(label) = self.label {
+			write!(f, " stolen from '{label}'")?;
+		}
+		if let Some(stolen_at) = self.stolen_at {
+			write!(f, " stolen at {stolen_at}")?;
+		}
+		Ok(())
+	}
+}
+
+static DEADLINE_HANDLER: RwLock<Option<fn(&OverdueSteal)>> = RwLock::new(None);
+
+/// Installs `handler` to be called, process wide, when a value stays stolen
+/// for too long, returning the previously installed one. Without a handler,
+/// a warning is printed on stderr.
+pub fn set_deadline_handler(handler: fn(&OverdueSteal)) -> Option<fn(&OverdueSteal)> {
+	DEADLINE_HANDLER
+		.write()
+		.unwrap_or_else(PoisonError::into_inner)
+		.replace(handler)
+}
+
+/// Removes the handler installed with [set_deadline_handler], going back to
+/// printing warnings on stderr.
+pub fn reset_deadline_handler() -> Option<fn(&OverdueSteal)> {
+	DEADLINE_HANDLER
+		.write()
+		.unwrap_or_else(PoisonError::into_inner)
+		.take()
+}
+
+pub(crate) fn report_overdue(overdue: &OverdueSteal) {
+	let handler = *DEADLINE_HANDLER
+		.read()
+		.unwrap_or_else(PoisonError::into_inner);
+	match handler {
+		Some(handler) => handler(overdue),
+		None => std::eprintln!("{overdue}"),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::{cell::RefCell, thread::sleep, time::Duration, vec::Vec};
+
+	use crate::{OverdueSteal, StealCell, set_deadline_handler};
+
+	std::thread_local! {
+		static RECORDED: RefCell<Vec<OverdueSteal>> = const { RefCell::new(Vec::new()) };
+	}
+
+	/// Only cells with a deadline report, so other tests are not affected.
+	fn record(f: impl FnOnce()) -> Vec<OverdueSteal> {
+		set_deadline_handler(|overdue| {
+			RECORDED.with_borrow_mut(|recorded| recorded.push(*overdue))
+		});
+		f();
+		RECORDED.take()
+	}
+
+	#[test]
+	fn reports_values_returned_after_the_deadline() {
+		let mut stealcell = StealCell::<usize>::new_named(1, "player.inventory");
+		stealcell.set_steal_deadline(Duration::from_millis(1));
+		let recorded = record(|| {
+			let stolen = stealcell.steal();
+			sleep(Duration::from_millis(5));
+			stealcell.return_stolen(stolen);
+		});
+
+		assert_eq!(recorded.len(), 1);
+		let overdue = recorded[0];
+		assert!(overdue.elapsed >= Duration::from_millis(5));
+		assert_eq!(overdue.deadline, Duration::from_millis(1));
+		#[cfg(not(feature = "slim_panics"))]
+		assert_eq!(overdue.type_name, "usize");
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		assert_eq!(overdue.label, Some("player.inventory"));
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		assert!(overdue.stolen_at.is_some());
+	}
+
+	#[test]
+	fn does_not_report_values_returned_in_time() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		stealcell.set_steal_deadline(Duration::from_secs(60));
+		let recorded = record(|| {
+			let stolen = stealcell.steal();
+			stealcell.return_stolen(stolen);
+			assert!(!stealcell.check_deadline());
+		});
+		assert!(recorded.is_empty());
+	}
+
+	#[test]
+	fn check_deadline_reports_values_still_out() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		stealcell.set_steal_deadline(Duration::from_millis(1));
+		let stolen = stealcell.steal();
+		let recorded = record(|| {
+			assert!(!StealCell::<usize>::new(1).check_deadline());
+			sleep(Duration::from_millis(5));
+			assert!(stealcell.check_deadline());
+		});
+		assert_eq!(recorded.len(), 1);
+		stealcell.clear_steal_deadline();
+		stealcell.return_stolen(stolen);
+	}
+}
@@ -0,0 +1,340 @@
+use core::{
+	fmt::{self, Debug, Formatter},
+	marker::PhantomData,
+	num::NonZeroUsize,
+	panic::Location,
+};
+
+use crate::{
+	DROP_BOMB, ShortTypeName, StealViolation, StolenFrom, ViolationKind, handle_violation,
+	next_cell_id, value_type_name,
+	violation::{misuse, violation},
+};
+
+/// The inverse of a [StealCell](crate::StealCell), starting out empty: an
+/// outside owner deposits a value into it for a while, and withdraws it with
+/// the [Receipt] it got for it. Meanwhile the value is used through the
+/// cell.
+pub struct DepositCell<T> {
+	value: Option<T>,
+	/// Assigned on the first deposit.
+	id: Option<NonZeroUsize>,
+	/// Incremented on every deposit.
+	generation: usize,
+	/// Included in panic messages, see [DepositCell::new_named].
+	label: Option<&'static str>,
+}
+
+/// Proof of a value deposited into a [DepositCell], which must be handed to
+/// [DepositCell::withdraw] to get the value back. It panics if dropped
+/// without withdrawing the value!
+#[must_use = "the receipt must be handed to withdraw or it will panic on drop"]
+pub struct Receipt<T> {
+	cell: NonZeroUsize,
+	generation: usize,
+	label: Option<&'static str>,
+	/// Where the value was deposited, reported when the receipt is dropped.
+	deposited_at: &'static Location<'static>,
+	value: PhantomData<fn() -> T>,
+}
+
+impl<T> DepositCell<T> {
+	/// Creates a cell with nothing in it.
+	pub fn new() -> Self {
+		Self {
+			value: None,
+			id: None,
+			generation: 0,
+			label: None,
+		}
+	}
+
+	/// Creates an empty cell with a label that is included in every panic
+	/// message about it.
+	pub fn new_named(name: &'static str) -> Self {
+		Self {
+			label: Some(name),
+			..Self::new()
+		}
+	}
+
+	/// Returns the label of the cell, if it has one.
+	pub fn label(&self) -> Option<&'static str> {
+		self.label
+	}
+
+	/// Returns `true` if there is a value deposited in the cell.
+	pub fn is_deposited(&self) -> bool {
+		self.value.is_some()
+	}
+
+	/// Places the value into the cell, handing out the receipt to withdraw it
+	/// with.
+	///
+	/// Panics if the cell is not empty!
+	#[track_caller]
+	pub fn deposit(&mut self, value: T) -> Receipt<T> {
+		if self.value.is_some() {
+			misuse!(
+				StealViolation::new(
+					ViolationKind::CellOccupied,
+					value_type_name::<T>(),
+					self.label,
+					None,
+				),
+				"can't deposit into a cell that is not empty! {}{}",
+				ShortTypeName(value_type_name::<T>()),
+				self.stolen_from(None)
+			);
+		}
+		self.generation = self.generation.wrapping_add(1);
+		self.value = Some(value);
+		Receipt {
+			cell: *self.id.get_or_insert_with(next_cell_id),
+			generation: self.generation,
+			label: self.label,
+			deposited_at: Location::caller(),
+			value: PhantomData,
+		}
+	}
+
+	/// Takes the deposited value back.
+	///
+	/// Panics if the receipt is of a different cell or of an earlier deposit,
+	/// or if the value was taken out of the cell meanwhile!
+	pub fn withdraw(&mut self, receipt: Receipt<T>) -> T {
+		let (kind, reason) = if self.id != Some(receipt.cell) {
+			(
+				ViolationKind::WrongCell,
+				"the receipt is of a different cell",
+			)
+		} else if self.generation != receipt.generation {
+			(ViolationKind::Stale, "the receipt is of an earlier deposit")
+		} else if let Some(value) = self.value.take() {
+			receipt.settle();
+			return value;
+		} else {
+			(ViolationKind::Lost, "it was taken out of the cell")
+		};
+		let violation = StealViolation::new(
+			kind,
+			value_type_name::<T>(),
+			receipt.label,
+			Some(receipt.deposited_at),
+		);
+		let stolen_from = receipt.stolen_from();
+		receipt.settle();
+		handle_violation(|| violation);
+		violation!(
+			violation,
+			"can't withdraw the deposited value, {}! {}{}",
+			reason,
+			ShortTypeName(value_type_name::<T>()),
+			stolen_from
+		)
+	}
+
+	/// Takes the deposited value out of the cell, after which the owner can't
+	/// withdraw it anymore.
+	pub fn take(&mut self) -> Option<T> {
+		self.value.take()
+	}
+
+	/// Returns a reference to the deposited value, if there is one.
+	pub fn get(&self) -> Option<&T> {
+		self.value.as_ref()
+	}
+
+	/// Returns a mutable reference to the deposited value, if there is one.
+	pub fn get_mut(&mut self) -> Option<&mut T> {
+		self.value.as_mut()
+	}
+
+	fn stolen_from(&self, stolen_at: Option<&'static Location<'static>>) -> StolenFrom {
+		StolenFrom {
+			label: self.label,
+			stolen_at,
+			ago: None,
+		}
+	}
+
+	#[track_caller]
+	fn nothing_deposited(&self) -> ! {
+		misuse!(
+			StealViolation::new(
+				ViolationKind::AlreadyStolen,
+				value_type_name::<T>(),
+				self.label,
+				None,
+			),
+			"nothing is deposited in the cell! {}{}",
+			ShortTypeName(value_type_name::<T>()),
+			self.stolen_from(None)
+		)
+	}
+}
+
+impl<T> Default for DepositCell<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T> AsRef<T> for DepositCell<T> {
+	/// Panics if nothing is deposited!
+	fn as_ref(&self) -> &T {
+		match &self.value {
+			Some(value) => value,
+			None => self.nothing_deposited(),
+		}
+	}
+}
+
+impl<T> AsMut<T> for DepositCell<T> {
+	/// Panics if nothing is deposited!
+	fn as_mut(&mut self) -> &mut T {
+		if self.value.is_none() {
+			self.nothing_deposited();
+		}
+		// PANIC SAFETY: Checked just above.
+		self.value.as_mut().unwrap()
+	}
+}
+
+/// Prints `DepositCell(<value>)`, or `DepositCell(<empty>)` if nothing is
+/// deposited.
+impl<T> Debug for DepositCell<T>
+where
+	T: Debug,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let empty = format_args!("<empty>");
+		let value: &dyn Debug = match &self.value {
+			Some(value) => value,
+			None => &empty,
+		};
+		f.debug_tuple("DepositCell").field(value).finish()
+	}
+}
+
+impl<T> Receipt<T> {
+	/// Returns where the value was deposited.
+	pub fn deposited_at(&self) -> &'static Location<'static> {
+		self.deposited_at
+	}
+
+	/// Consumes the receipt without panicking.
+	fn settle(self) {
+		core::mem::forget(self);
+	}
+
+	fn stolen_from(&self) -> StolenFrom {
+		StolenFrom {
+			label: self.label,
+			stolen_at: Some(self.deposited_at),
+			ago: None,
+		}
+	}
+}
+
+impl<T> Drop for Receipt<T> {
+	/// Behaves just like losing a stolen value, see the `Drop` of
+	/// [Stolen](crate::Stolen).
+	fn drop(&mut self) {
+		let violation = StealViolation::new(
+			ViolationKind::Lost,
+			value_type_name::<T>(),
+			self.label,
+			Some(self.deposited_at),
+		);
+		if handle_violation(|| violation) || !DROP_BOMB {
+			return;
+		}
+
+		#[cfg(not(any(feature = "no_std", feature = "abort_on_loss")))]
+		if std::thread::panicking() {
+			return;
+		}
+
+		violation!(
+			violation,
+			"You've dropped a receipt without withdrawing the deposited value! {}{}",
+			ShortTypeName(value_type_name::<T>()),
+			self.stolen_from()
+		);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	struct Guest {
+		host_state: DepositCell<Vec<u8>>,
+	}
+
+	impl Guest {
+		fn callback(&mut self) {
+			self.host_state.as_mut().push(2);
+		}
+	}
+
+	#[test]
+	fn lends_a_value_in() {
+		let mut guest = Guest {
+			host_state: DepositCell::new_named("host_state"),
+		};
+		assert!(!guest.host_state.is_deposited());
+		let receipt = guest.host_state.deposit(vec![1]);
+		assert_eq!(receipt.deposited_at().file(), file!());
+		assert_eq!(format!("{:?}", guest.host_state), "DepositCell([1])");
+		guest.callback();
+		assert_eq!(guest.host_state.as_ref(), &[1, 2]);
+		assert_eq!(guest.host_state.withdraw(receipt), [1, 2]);
+		assert_eq!(format!("{:?}", guest.host_state), "DepositCell(<empty>)");
+
+		let receipt = guest.host_state.deposit(vec![3]);
+		assert_eq!(guest.host_state.withdraw(receipt), [3]);
+	}
+
+	#[test]
+	#[should_panic(expected = "can't withdraw the deposited value, it was taken out")]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_when_withdrawing_from_an_emptied_cell() {
+		let mut cell = DepositCell::new();
+		let receipt = cell.deposit(1);
+		assert_eq!(cell.take(), Some(1));
+		cell.withdraw(receipt);
+	}
+
+	#[test]
+	#[should_panic(expected = "the receipt is of a different cell")]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_when_withdrawing_from_another_cell() {
+		let mut a = DepositCell::new();
+		let mut b = DepositCell::new();
+		let receipt = a.deposit(1);
+		b.deposit(2).settle();
+		b.withdraw(receipt);
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, not(feature = "release_disarm")))]
+	#[should_panic(expected = "dropped a receipt without withdrawing")]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_when_the_receipt_is_dropped() {
+		let mut cell = DepositCell::new();
+		drop(cell.deposit(1));
+	}
+
+	#[test]
+	#[should_panic(expected = "nothing is deposited")]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	fn panics_when_reading_an_empty_cell() {
+		let cell = DepositCell::<usize>::new();
+		let _ = cell.as_ref();
+	}
+}
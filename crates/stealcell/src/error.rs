@@ -0,0 +1,386 @@
+use core::{
+	fmt::{self, Debug, Display, Formatter},
+	panic::Location,
+};
+
+use crate::{ShortTypeName, Stolen, value_type_name};
+
+/// Returned by [StealCell::try_steal](crate::StealCell::try_steal) when the value can't be stolen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StealError {
+	/// The cell is empty, its value was already stolen.
+	AlreadyStolen {
+		/// The type name of the value in the cell.
+		type_name: &'static str,
+		/// Where the value was stolen from the cell, if it was stolen and not
+		/// just taken.
+		stolen_at: Option<&'static Location<'static>>,
+		/// The label of the cell, see [StealCell::new_named](crate::StealCell::new_named).
+		label: Option<&'static str>,
+	},
+	/// The value stolen from the cell was lost without being returned. Only
+	/// tracked with the `poison` feature.
+	Poisoned {
+		/// The type name of the value in the cell.
+		type_name: &'static str,
+		/// Where the lost value was stolen from the cell.
+		stolen_at: Option<&'static Location<'static>>,
+		/// The label of the cell, see [StealCell::new_named](crate::StealCell::new_named).
+		label: Option<&'static str>,
+	},
+}
+
+impl Display for StealError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::AlreadyStolen {
+				type_name,
+				stolen_at,
+				label,
+			} => {
+				let type_name = ShortTypeName(type_name);
+				match label {
+					Some(label) => write!(f, "value already stolen from '{label}': {type_name}")?,
+					None => write!(f, "value already stolen from: {type_name}")?,
+				}
+				if let Some(stolen_at) = stolen_at {
+					write!(f, ", first stolen at {stolen_at}")?;
+				}
+				Ok(())
+			}
+			Self::Poisoned {
+				type_name,
+				stolen_at,
+				label,
+			} => {
+				let type_name = ShortTypeName(type_name);
+				match label {
+					Some(label) => {
+						write!(f, "value was lost, cell '{label}' is poisoned: {type_name}")?
+					}
+					None => write!(f, "value was lost, cell is poisoned: {type_name}")?,
+				}
+				if let Some(stolen_at) = stolen_at {
+					write!(f, ", lost value stolen at {stolen_at}")?;
+				}
+				Ok(())
+			}
+		}
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for StealError {}
+
+/// Returned by [StealCell::try_return_stolen](crate::StealCell::try_return_stolen) when the stolen value couldn't
+/// be returned.
+pub enum ReturnError<T> {
+	/// The cell already has a value in it. The stolen value is handed back
+	/// to you, still armed, so you can decide what to do with it.
+	CellOccupied(Stolen<T>),
+	/// The stolen value belongs to a different cell. It's handed back to you,
+	/// still armed.
+	WrongCell(Stolen<T>),
+	/// The cell was refilled since the value was stolen, returning it would
+	/// bring back outdated state. It's handed back to you, still armed.
+	Stale(Stolen<T>),
+	/// The stolen value was already returned, there is nothing to return.
+	AlreadyReturned,
+}
+
+impl<T> Debug for ReturnError<T> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::CellOccupied(_) => f.write_str("CellOccupied(..)"),
+			Self::WrongCell(_) => f.write_str("WrongCell(..)"),
+			Self::Stale(_) => f.write_str("Stale(..)"),
+			Self::AlreadyReturned => f.write_str("AlreadyReturned"),
+		}
+	}
+}
+
+impl<T> Display for ReturnError<T> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let type_name = ShortTypeName(value_type_name::<T>());
+		let (label, reason) = match self {
+			Self::CellOccupied(stolen) => (stolen.label(), "this cell is not empty"),
+			Self::WrongCell(stolen) => (stolen.label(), "it was stolen from a different cell"),
+			Self::Stale(stolen) => (stolen.label(), "the cell was refilled since it was stolen"),
+			Self::AlreadyReturned => (None, "it was already returned"),
+		};
+		match label {
+			Some(label) => write!(f, "trying to return a value stolen from '{label}'")?,
+			None => f.write_str("trying to return a stolen value")?,
+		}
+		write!(f, ", but {reason}! {type_name}")
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> std::error::Error for ReturnError<T> {}
+
+/// Returned by [StealCell::transfer_to](crate::StealCell::transfer_to) when the value can't be
+/// moved. Neither cell is changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferError {
+	/// The source cell is empty, the reason is the same as when stealing
+	/// from it.
+	SourceStolen(StealError),
+	/// The destination cell already has a value in it.
+	DestinationOccupied {
+		/// The type name of the value in the cells.
+		type_name: &'static str,
+		/// The label of the destination cell, see [StealCell::new_named](crate::StealCell::new_named).
+		label: Option<&'static str>,
+	},
+}
+
+impl Display for TransferError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::SourceStolen(error) => write!(f, "nothing to transfer, {error}"),
+			Self::DestinationOccupied { type_name, label } => {
+				let type_name = ShortTypeName(type_name);
+				match label {
+					Some(label) => write!(
+						f,
+						"can't transfer into '{label}', it's not empty: {type_name}"
+					),
+					None => write!(
+						f,
+						"can't transfer into a cell that is not empty: {type_name}"
+					),
+				}
+			}
+		}
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for TransferError {}
+
+/// Returned by [StealCell::try_swap](crate::StealCell::try_swap) when either cell is empty, with
+/// the reasons they are. Neither cell is changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapError {
+	/// The cell `try_swap` was called on is empty.
+	ThisStolen(StealError),
+	/// The other cell is empty.
+	OtherStolen(StealError),
+	/// Both cells are empty, this one first.
+	BothStolen(StealError, StealError),
+}
+
+impl Display for SwapError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::ThisStolen(error) => write!(f, "can't swap out of an empty cell, {error}"),
+			Self::OtherStolen(error) => write!(f, "can't swap into an empty cell, {error}"),
+			Self::BothStolen(this, other) => {
+				write!(f, "can't swap between empty cells, {this}, and {other}")
+			}
+		}
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for SwapError {}
+
+/// Returned by [StealEach::try_steal_each](crate::StealEach::try_steal_each) when one of the
+/// cells is already stolen. The cells before it are returned to by then.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StealEachError {
+	/// The index of the first cell that couldn't be stolen from.
+	pub index: usize,
+	/// Why it couldn't be stolen from.
+	pub error: StealError,
+}
+
+impl Display for StealEachError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "can't steal cell {}, {}", self.index, self.error)
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for StealEachError {}
+
+#[cfg(test)]
+mod test {
+	use core::panic::Location;
+
+	#[cfg(not(feature = "slim_panics"))]
+	use crate::ReturnError;
+	#[cfg(not(all(feature = "slim_panics", feature = "no_std")))]
+	use crate::StealCell;
+	use crate::{StealError, TransferError};
+
+	#[test]
+	fn steal_error_displays_the_type_name() {
+		let error = StealError::AlreadyStolen {
+			type_name: "usize",
+			stolen_at: None,
+			label: None,
+		};
+		assert_eq!(error.to_string(), "value already stolen from: usize");
+
+		let location = Location::caller();
+		let error = StealError::AlreadyStolen {
+			type_name: "usize",
+			stolen_at: Some(location),
+			label: None,
+		};
+		assert_eq!(
+			error.to_string(),
+			format!("value already stolen from: usize, first stolen at {location}")
+		);
+	}
+
+	#[test]
+	#[cfg(not(feature = "slim_panics"))]
+	fn return_error_displays_the_type_name() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		stealcell.set(2).unwrap();
+		let Err(error) = stealcell.try_return_stolen(stolen) else {
+			panic!("expected the cell to be occupied");
+		};
+		assert_eq!(
+			error.to_string(),
+			"trying to return a stolen value, but this cell is not empty! usize"
+		);
+		let ReturnError::CellOccupied(stolen) = error else {
+			unreachable!()
+		};
+		stealcell.take();
+		stealcell.return_stolen_unchecked(stolen);
+
+		assert_eq!(
+			ReturnError::<usize>::AlreadyReturned.to_string(),
+			"trying to return a stolen value, but it was already returned! usize"
+		);
+	}
+
+	#[test]
+	#[cfg(not(feature = "slim_panics"))]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn return_error_displays_the_type_name_of_foreign_and_stale_values() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		let mut other = StealCell::<usize>::empty();
+		let Err(error) = other.try_return_stolen(stolen) else {
+			panic!("expected the cell to be the wrong one");
+		};
+		assert_eq!(
+			error.to_string(),
+			"trying to return a stolen value, but it was stolen from a different cell! usize"
+		);
+		let ReturnError::WrongCell(stolen) = error else {
+			unreachable!()
+		};
+
+		stealcell.set(2).unwrap();
+		stealcell.take();
+		let Err(error) = stealcell.try_return_stolen(stolen) else {
+			panic!("expected the value to be stale");
+		};
+		assert_eq!(
+			error.to_string(),
+			"trying to return a stolen value, but the cell was refilled since it was stolen! usize"
+		);
+		let ReturnError::Stale(stolen) = error else {
+			unreachable!()
+		};
+		stealcell.return_stolen_unchecked(stolen);
+	}
+
+	#[test]
+	fn transfer_error_displays_the_type_name() {
+		let error = TransferError::SourceStolen(StealError::AlreadyStolen {
+			type_name: "usize",
+			stolen_at: None,
+			label: None,
+		});
+		assert_eq!(
+			error.to_string(),
+			"nothing to transfer, value already stolen from: usize"
+		);
+
+		let error = TransferError::DestinationOccupied {
+			type_name: "usize",
+			label: None,
+		};
+		assert_eq!(
+			error.to_string(),
+			"can't transfer into a cell that is not empty: usize"
+		);
+		let error = TransferError::DestinationOccupied {
+			type_name: "usize",
+			label: Some("slot"),
+		};
+		assert_eq!(
+			error.to_string(),
+			"can't transfer into 'slot', it's not empty: usize"
+		);
+	}
+
+	#[test]
+	#[cfg(not(feature = "slim_panics"))]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn errors_include_the_label_of_the_cell() {
+		let location = Location::caller();
+		let error = StealError::AlreadyStolen {
+			type_name: "usize",
+			stolen_at: Some(location),
+			label: Some("player.inventory"),
+		};
+		assert_eq!(
+			error.to_string(),
+			format!(
+				"value already stolen from 'player.inventory': usize, first stolen at {location}"
+			)
+		);
+		let error = StealError::Poisoned {
+			type_name: "usize",
+			stolen_at: None,
+			label: Some("player.inventory"),
+		};
+		assert_eq!(
+			error.to_string(),
+			"value was lost, cell 'player.inventory' is poisoned: usize"
+		);
+
+		let mut stealcell = StealCell::<usize>::new_named(1, "player.inventory");
+		let stolen = stealcell.steal();
+		stealcell.set(2).unwrap();
+		let Err(error) = stealcell.try_return_stolen(stolen) else {
+			panic!("expected the cell to be occupied");
+		};
+		assert_eq!(
+			error.to_string(),
+			"trying to return a value stolen from 'player.inventory', but this cell is not empty! usize"
+		);
+		let ReturnError::CellOccupied(stolen) = error else {
+			unreachable!()
+		};
+		stealcell.take();
+		stealcell.return_stolen_unchecked(stolen);
+	}
+
+	#[test]
+	#[cfg(not(feature = "no_std"))]
+	fn can_be_used_as_std_errors() {
+		fn steal_twice(stealcell: &mut StealCell<usize>) -> Result<(), Box<dyn std::error::Error>> {
+			let stolen = stealcell.try_steal()?;
+			let result = stealcell.try_steal().map(|_| ());
+			stealcell.return_stolen(stolen);
+			result?;
+			Ok(())
+		}
+
+		let mut stealcell = StealCell::<usize>::new(1);
+		let error = steal_twice(&mut stealcell).unwrap_err();
+		assert!(error.to_string().starts_with("value already stolen from:"));
+		assert!(!stealcell.is_stolen());
+	}
+}
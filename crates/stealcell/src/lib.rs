@@ -1,5 +1,68 @@
 #![cfg_attr(all(feature = "no_std", not(test)), no_std)]
 
+#[cfg(any(feature = "poison", feature = "alloc", not(feature = "no_std")))]
+extern crate alloc;
+#[cfg(any(
+	feature = "violation_handler",
+	feature = "debug_thread_affinity",
+	feature = "steal_timing",
+	feature = "test_util",
+	feature = "salvage",
+	feature = "typed_panics"
+))]
+extern crate std;
+
+mod branded;
+mod cell_state;
+mod consume_cell;
+#[cfg(feature = "deadline")]
+mod deadline;
+#[cfg(any(target_has_atomic = "ptr", feature = "portable_atomic"))]
+mod deposit_cell;
+mod error;
+mod named_cell;
+mod placeholder;
+#[cfg(any(target_has_atomic = "ptr", feature = "portable_atomic"))]
+mod proof;
+#[cfg(feature = "test_util")]
+mod registry;
+mod short_type_name;
+mod split_view;
+mod steal_all;
+mod steal_guard;
+mod steal_macro;
 mod stealcell;
+mod violation;
+
+#[cfg(not(feature = "no_std"))]
+pub mod r#async;
+#[cfg(all(
+	any(feature = "alloc", not(feature = "no_std")),
+	any(target_has_atomic = "ptr", feature = "portable_atomic")
+))]
+pub mod sync;
+pub mod typed;
 
+pub use branded::*;
+pub use cell_state::*;
+pub use consume_cell::*;
+#[cfg(feature = "deadline")]
+pub use deadline::*;
+#[cfg(any(target_has_atomic = "ptr", feature = "portable_atomic"))]
+pub use deposit_cell::*;
+pub use error::*;
+pub use named_cell::*;
+pub use placeholder::*;
+#[cfg(any(target_has_atomic = "ptr", feature = "portable_atomic"))]
+pub use proof::*;
+#[cfg(feature = "test_util")]
+pub use registry::*;
+pub(crate) use short_type_name::*;
+pub use steal_all::*;
+pub use steal_guard::*;
+pub use steal_macro::*;
 pub use stealcell::*;
+pub use violation::*;
+
+#[cfg(feature = "derive")]
+pub use stealcell_derive::*;
@@ -0,0 +1,269 @@
+#[cfg(not(feature = "no_std"))]
+use core::ops::{Deref, DerefMut};
+use core::{
+	fmt::{self, Debug, Formatter},
+	marker::PhantomData,
+};
+
+use crate::Stolen;
+
+/// A newtype cell made with [steal_cell], named after the newtype.
+pub trait NamedCell {
+	/// The type of the value in the cell.
+	type Value;
+
+	/// The name of the newtype, which is also the label of the cell.
+	const NAME: &'static str;
+}
+
+/// A value stolen from a [NamedCell], which can only be returned to that
+/// kind of cell. Like [Stolen], it panics if dropped without returning it!
+#[must_use = "a stolen value must be returned with return_stolen or it will panic on drop"]
+pub struct NamedStolen<C: NamedCell> {
+	stolen: Stolen<C::Value>,
+	cell: PhantomData<fn() -> C>,
+}
+
+impl<C: NamedCell> NamedStolen<C> {
+	/// Only meant for [steal_cell], so that the stolen values of a named cell
+	/// keep its type.
+	#[doc(hidden)]
+	pub fn __new(stolen: Stolen<C::Value>) -> Self {
+		Self {
+			stolen,
+			cell: PhantomData,
+		}
+	}
+
+	/// Returns a reference to the stolen value, see [Stolen::get].
+	pub fn get(&self) -> &C::Value {
+		self.stolen.get()
+	}
+
+	/// Returns a mutable reference to the stolen value, see
+	/// [Stolen::get_mut].
+	pub fn get_mut(&mut self) -> &mut C::Value {
+		self.stolen.get_mut()
+	}
+
+	/// Hands over the plain stolen value, which can still be returned to the
+	/// inner [StealCell](crate::StealCell) of the named cell.
+	pub fn into_stolen(self) -> Stolen<C::Value> {
+		self.stolen
+	}
+
+	/// Keeps the stolen value for good, see [Stolen::into_inner].
+	pub fn into_inner(self) -> C::Value {
+		self.stolen.into_inner()
+	}
+
+	/// Drops the stolen value without returning it, see [Stolen::discard].
+	pub fn discard(self) {
+		self.stolen.discard();
+	}
+}
+
+/// Prints like the plain [Stolen] value.
+impl<C: NamedCell> Debug for NamedStolen<C>
+where
+	C::Value: Debug,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		self.stolen.fmt(f)
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<C: NamedCell> Deref for NamedStolen<C> {
+	type Target = C::Value;
+
+	fn deref(&self) -> &Self::Target {
+		self.get()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<C: NamedCell> DerefMut for NamedStolen<C> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.get_mut()
+	}
+}
+
+/// Makes a newtype around a [StealCell](crate::StealCell) labeled with the
+/// name of the newtype, so that every panic and error message about it says
+/// which cell it was. The values stolen from it are [NamedStolen] values,
+/// which can't be returned to a different named cell.
+///
+/// The newtype derefs to the inner cell for the rest of the API, with
+/// `steal`, `try_steal`, `expect_steal`, `return_stolen` and
+/// `try_return_stolen` taking and handing out [NamedStolen] values instead.
+///
+/// Used like `steal_cell!(pub PlayerSlot: Player);`, attributes like derives
+/// are put on the newtype.
+#[macro_export]
+macro_rules! steal_cell {
+	($(#[$attribute:meta])* $vis:vis $name:ident: $value:ty $(;)?) => {
+		$(#[$attribute])*
+		$vis struct $name($crate::StealCell<$value>);
+
+		impl $crate::NamedCell for $name {
+			type Value = $value;
+
+			const NAME: &'static str = stringify!($name);
+		}
+
+		impl $name {
+			/// Creates a cell with the value in it.
+			$vis fn new(value: $value) -> Self {
+				Self($crate::StealCell::new_named(value, stringify!($name)))
+			}
+
+			/// Creates a cell with nothing in it.
+			$vis fn empty() -> Self {
+				let mut stealcell = $crate::StealCell::<$value>::empty();
+				stealcell.set_label(stringify!($name));
+				Self(stealcell)
+			}
+
+			/// Steals the value, see [StealCell::steal]($crate::StealCell::steal).
+			#[track_caller]
+			$vis fn steal(&mut self) -> $crate::NamedStolen<Self> {
+				$crate::NamedStolen::<Self>::__new(self.0.steal())
+			}
+
+			/// Steals the value, see
+			/// [StealCell::try_steal]($crate::StealCell::try_steal).
+			#[track_caller]
+			$vis fn try_steal(
+				&mut self,
+			) -> ::core::result::Result<$crate::NamedStolen<Self>, $crate::StealError> {
+				self.0.try_steal().map($crate::NamedStolen::<Self>::__new)
+			}
+
+			/// Steals the value, see
+			/// [StealCell::expect_steal]($crate::StealCell::expect_steal).
+			#[track_caller]
+			$vis fn expect_steal(&mut self, msg: &str) -> $crate::NamedStolen<Self> {
+				$crate::NamedStolen::<Self>::__new(self.0.expect_steal(msg))
+			}
+
+			/// Returns the stolen value, see
+			/// [StealCell::return_stolen]($crate::StealCell::return_stolen).
+			$vis fn return_stolen(&mut self, stolen: $crate::NamedStolen<Self>) {
+				self.0.return_stolen(stolen.into_stolen());
+			}
+
+			/// Returns the stolen value, see
+			/// [StealCell::try_return_stolen]($crate::StealCell::try_return_stolen).
+			/// A value handed back in the error is a plain stolen value.
+			$vis fn try_return_stolen(
+				&mut self,
+				stolen: $crate::NamedStolen<Self>,
+			) -> ::core::result::Result<(), $crate::ReturnError<$value>> {
+				self.0.try_return_stolen(stolen.into_stolen())
+			}
+
+			/// Hands over the inner cell.
+			$vis fn into_cell(self) -> $crate::StealCell<$value> {
+				self.0
+			}
+		}
+
+		impl ::core::ops::Deref for $name {
+			type Target = $crate::StealCell<$value>;
+
+			fn deref(&self) -> &Self::Target {
+				&self.0
+			}
+		}
+
+		impl ::core::ops::DerefMut for $name {
+			fn deref_mut(&mut self) -> &mut Self::Target {
+				&mut self.0
+			}
+		}
+	};
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{NamedCell, ReturnError, StealError};
+
+	#[derive(Debug, PartialEq)]
+	pub(crate) struct Player {
+		health: u32,
+	}
+
+	crate::steal_cell!(
+		/// Where the player is kept.
+		#[derive(Debug)]
+		pub(crate) PlayerSlot: Player
+	);
+	crate::steal_cell!(BenchSlot: Player;);
+
+	#[test]
+	fn steals_and_returns() {
+		let mut slot = PlayerSlot::new(Player { health: 10 });
+		let mut player = slot.steal();
+		assert!(slot.is_stolen());
+		player.get_mut().health -= 1;
+		assert_eq!(player.get().health, 9);
+		slot.return_stolen(player);
+		assert_eq!(slot.get(), Some(&Player { health: 9 }));
+
+		let player = slot.try_steal().unwrap();
+		assert!(slot.try_steal().is_err());
+		assert!(slot.try_return_stolen(player).is_ok());
+		let player = slot.expect_steal("moving the player");
+		slot.return_stolen(player);
+		assert_eq!(slot.into_cell().into_inner(), Some(Player { health: 9 }));
+	}
+
+	#[test]
+	fn labels_the_cell_with_its_name() {
+		assert_eq!(PlayerSlot::NAME, "PlayerSlot");
+		assert_eq!(<BenchSlot as NamedCell>::NAME, "BenchSlot");
+
+		let label = cfg!(any(debug_assertions, feature = "diagnostics")).then_some("PlayerSlot");
+		let mut slot = PlayerSlot::empty();
+		assert_eq!(slot.label(), label);
+		slot.set(Player { health: 1 }).unwrap();
+		let player = slot.steal();
+		let error = slot.try_steal().unwrap_err();
+		assert!(matches!(
+			error,
+			StealError::AlreadyStolen { label: stolen_from, .. } if stolen_from == label
+		));
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		assert!(
+			error
+				.to_string()
+				.starts_with("value already stolen from 'PlayerSlot':")
+		);
+		let player = player.into_stolen();
+		assert_eq!(player.label(), label);
+		player.return_to(&mut slot);
+		assert!(!slot.is_stolen());
+	}
+
+	#[test]
+	fn hands_back_values_that_can_not_be_returned() {
+		let mut slot = BenchSlot::new(Player { health: 3 });
+		let player = slot.steal();
+		slot.set(Player { health: 4 }).unwrap();
+		let Err(ReturnError::CellOccupied(player)) = slot.try_return_stolen(player) else {
+			panic!("expected the cell to be occupied");
+		};
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		assert_eq!(player.label(), Some("BenchSlot"));
+		player.discard();
+
+		let mut slot = BenchSlot::empty();
+		slot.set(Player { health: 5 }).unwrap();
+		let player = slot.try_steal().unwrap();
+		slot.return_stolen(player);
+		let player = slot.expect_steal("benching the player");
+		slot.return_stolen(player);
+		assert_eq!(slot.into_cell().into_inner(), Some(Player { health: 5 }));
+	}
+}
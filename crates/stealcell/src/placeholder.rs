@@ -0,0 +1,53 @@
+/// Types with a cheap placeholder to leave in a cell while their value is
+/// stolen, see [StealCell::steal_replace_placeholder](crate::StealCell::steal_replace_placeholder).
+///
+/// Unlike [Default], the placeholder doesn't have to be a meaningful value,
+/// only a cheap one, ideally without allocating. With the `derive` feature,
+/// it can be derived for structs whose fields all have placeholders.
+pub trait StealPlaceholder {
+	/// Makes a new placeholder.
+	fn placeholder() -> Self;
+}
+
+impl<T> StealPlaceholder for Option<T> {
+	fn placeholder() -> Self {
+		None
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> StealPlaceholder for std::vec::Vec<T> {
+	fn placeholder() -> Self {
+		std::vec::Vec::new()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl StealPlaceholder for std::string::String {
+	fn placeholder() -> Self {
+		std::string::String::new()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<K, V> StealPlaceholder for std::collections::HashMap<K, V> {
+	fn placeholder() -> Self {
+		std::collections::HashMap::new()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn placeholders_are_empty() {
+		assert_eq!(Option::<usize>::placeholder(), None);
+		#[cfg(not(feature = "no_std"))]
+		{
+			assert!(std::vec::Vec::<usize>::placeholder().is_empty());
+			assert!(std::string::String::placeholder().is_empty());
+			assert!(std::collections::HashMap::<usize, usize>::placeholder().is_empty());
+		}
+	}
+}
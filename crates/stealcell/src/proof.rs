@@ -0,0 +1,122 @@
+use core::{num::NonZeroUsize, panic::Location};
+
+use crate::{
+	DROP_BOMB, ShortTypeName, StealViolation, StolenFrom, ViolationKind, handle_violation,
+	violation::violation,
+};
+
+/// Proof that a value stolen with
+/// [StealCell::steal_with_proof](crate::StealCell::steal_with_proof) will be
+/// returned, only consumed by returning it with
+/// [StealCell::return_stolen_with_proof](crate::StealCell::return_stolen_with_proof)
+/// to the same cell. Taking one as a parameter makes the caller promise to
+/// return the value.
+///
+/// It panics if dropped, even if the value was returned without it! It's
+/// not zero-sized, as it remembers which cell it belongs to.
+#[must_use = "the proof must be handed to return_stolen_with_proof or it will panic on drop"]
+pub struct MustReturn {
+	/// `None` when the cell doesn't keep track of its id.
+	pub(crate) cell: Option<NonZeroUsize>,
+	pub(crate) type_name: &'static str,
+	pub(crate) label: Option<&'static str>,
+	pub(crate) stolen_at: Option<&'static Location<'static>>,
+}
+
+impl MustReturn {
+	/// Consumes the proof without panicking, once the value is returned.
+	pub(crate) fn settle(self) {
+		core::mem::forget(self);
+	}
+
+	pub(crate) fn stolen_from(&self) -> StolenFrom {
+		StolenFrom {
+			label: self.label,
+			stolen_at: self.stolen_at,
+			ago: None,
+		}
+	}
+}
+
+impl Drop for MustReturn {
+	/// Behaves just like losing a stolen value, see the `Drop` of
+	/// [Stolen](crate::Stolen).
+	fn drop(&mut self) {
+		let violation = StealViolation::new(
+			ViolationKind::Lost,
+			self.type_name,
+			self.label,
+			self.stolen_at,
+		);
+		if handle_violation(|| violation) || !DROP_BOMB {
+			return;
+		}
+
+		#[cfg(not(any(feature = "no_std", feature = "abort_on_loss")))]
+		if std::thread::panicking() {
+			return;
+		}
+
+		violation!(
+			violation,
+			"You've dropped the proof of a steal without returning the value with it! {}{}",
+			ShortTypeName(self.type_name),
+			self.stolen_from()
+		);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::StealCell;
+
+	fn spend(stealcell: &mut StealCell<usize>, proof: super::MustReturn) {
+		let stolen = stealcell.steal();
+		stealcell.return_stolen_with_proof(stolen, proof);
+	}
+
+	#[test]
+	fn is_consumed_by_the_return() {
+		let mut stealcell = StealCell::new(1);
+		let (mut stolen, proof) = stealcell.steal_with_proof();
+		*stolen.get_mut() += 1;
+		stealcell.return_stolen_with_proof(stolen, proof);
+		assert_eq!(stealcell.get(), Some(&2));
+	}
+
+	#[test]
+	fn can_be_passed_on() {
+		let mut stealcell = StealCell::new(1);
+		let (stolen, proof) = stealcell.steal_with_proof();
+		stealcell.return_stolen(stolen);
+		spend(&mut stealcell, proof);
+		assert!(!stealcell.is_stolen());
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, not(feature = "release_disarm")))]
+	#[should_panic(expected = "dropped the proof of a steal")]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_when_dropped() {
+		let mut stealcell = StealCell::new(1);
+		let (stolen, proof) = stealcell.steal_with_proof();
+		stealcell.return_stolen(stolen);
+		drop(proof);
+	}
+
+	/// The values are lost while unwinding, which aborts with `no_std`.
+	#[test]
+	#[cfg(not(feature = "no_std"))]
+	#[should_panic(expected = "with the proof of a different cell")]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn panics_when_returned_with_the_proof_of_another_cell() {
+		let mut a = StealCell::new(1);
+		let mut b = StealCell::new(2);
+		let (stolen_a, _proof_a) = a.steal_with_proof();
+		let (_stolen_b, proof_b) = b.steal_with_proof();
+		a.return_stolen_with_proof(stolen_a, proof_b);
+	}
+}
@@ -0,0 +1,89 @@
+use core::{
+	fmt::{self, Display, Formatter},
+	num::NonZeroUsize,
+	panic::Location,
+};
+use std::{
+	collections::BTreeMap,
+	sync::{Mutex, PoisonError},
+	vec::Vec,
+};
+
+use crate::{ShortTypeName, StolenFrom};
+
+/// A value stolen from a tracked cell that wasn't returned yet, see
+/// [StealCell::tracked](crate::StealCell::tracked).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutstandingSteal {
+	/// The type name of the stolen value.
+	pub type_name: &'static str,
+	/// The label of the cell, see
+	/// [StealCell::new_named](crate::StealCell::new_named).
+	pub label: Option<&'static str>,
+	/// Where the value was stolen.
+	pub stolen_at: Option<&'static Location<'static>>,
+}
+
+impl Display for OutstandingSteal {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let stolen_from = StolenFrom {
+			label: self.label,
+			stolen_at: self.stolen_at,
+			ago: None,
+		};
+		write!(f, "{}{stolen_from}", ShortTypeName(self.type_name))
+	}
+}
+
+/// Outstanding steals of every tracked cell, by cell id and generation.
+static OUTSTANDING: Mutex<BTreeMap<(NonZeroUsize, usize), OutstandingSteal>> =
+	Mutex::new(BTreeMap::new());
+
+fn outstanding() -> std::sync::MutexGuard<'static, BTreeMap<(NonZeroUsize, usize), OutstandingSteal>>
+{
+	OUTSTANDING.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Lists every value stolen from a tracked cell that wasn't returned yet, on
+/// any thread, in the order the cells were created.
+pub fn outstanding_steals() -> Vec<OutstandingSteal> {
+	outstanding().values().copied().collect()
+}
+
+/// Panics listing every value stolen from a tracked cell that wasn't
+/// returned yet, on any thread. Meant to be called at the end of tests.
+#[track_caller]
+pub fn assert_all_returned() {
+	let outstanding = outstanding_steals();
+	if outstanding.is_empty() {
+		return;
+	}
+	let mut message = std::format!("{} stolen values were not returned:", outstanding.len());
+	for steal in &outstanding {
+		message.push_str(&std::format!("\n  {steal}"));
+	}
+	panic!("{message}");
+}
+
+/// Registers the steals of a tracked cell, unregistering all of them when
+/// the cell is dropped.
+#[derive(Debug)]
+pub(crate) struct Tracking(pub(crate) NonZeroUsize);
+
+impl Tracking {
+	pub(crate) fn stolen(&self, generation: usize, steal: OutstandingSteal) {
+		outstanding().insert((self.0, generation), steal);
+	}
+}
+
+/// Unregisters a value stolen from a tracked cell once it's returned, to any
+/// cell.
+pub(crate) fn returned(cell: NonZeroUsize, generation: usize) {
+	outstanding().remove(&(cell, generation));
+}
+
+impl Drop for Tracking {
+	fn drop(&mut self) {
+		outstanding().retain(|(cell, _), _| *cell != self.0);
+	}
+}
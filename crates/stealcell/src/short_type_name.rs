@@ -0,0 +1,62 @@
+use core::fmt::{self, Display, Formatter, Write};
+
+/// The type name of `T`, or an empty string with the `slim_panics` feature,
+/// so that type names don't end up in the binary.
+#[cfg_attr(feature = "slim_panics", expect(clippy::extra_unused_type_parameters))]
+pub(crate) fn value_type_name<T: ?Sized>() -> &'static str {
+	#[cfg(feature = "slim_panics")]
+	return "";
+	#[cfg(not(feature = "slim_panics"))]
+	core::any::type_name::<T>()
+}
+
+/// Formats a type name without module paths, like `Vec<Thing>` instead of
+/// `alloc::vec::Vec<my_crate::module::Thing>`, to keep messages short.
+pub(crate) struct ShortTypeName(pub(crate) &'static str);
+
+impl Display for ShortTypeName {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let mut rest = self.0;
+		while !rest.is_empty() {
+			let path_len = rest
+				.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+				.unwrap_or(rest.len());
+			let (path, after) = rest.split_at(path_len);
+			f.write_str(path.rsplit("::").next().unwrap_or(path))?;
+
+			let mut chars = after.chars();
+			if let Some(delimiter) = chars.next() {
+				f.write_char(delimiter)?;
+			}
+			rest = chars.as_str();
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use core::any::type_name;
+
+	use super::ShortTypeName;
+
+	struct Thing;
+
+	#[test]
+	fn trims_module_paths() {
+		assert_eq!(ShortTypeName(type_name::<Thing>()).to_string(), "Thing");
+		assert_eq!(ShortTypeName(type_name::<usize>()).to_string(), "usize");
+		assert_eq!(
+			ShortTypeName(type_name::<Vec<Thing>>()).to_string(),
+			"Vec<Thing>"
+		);
+		assert_eq!(
+			ShortTypeName(type_name::<(Option<Thing>, &str, [u8; 4])>()).to_string(),
+			"(Option<Thing>, &str, [u8; 4])"
+		);
+		assert_eq!(
+			ShortTypeName(type_name::<Box<dyn core::fmt::Debug>>()).to_string(),
+			"Box<dyn Debug>"
+		);
+	}
+}
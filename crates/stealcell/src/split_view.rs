@@ -0,0 +1,97 @@
+/// Steals a field of a struct into a [StealGuard](crate::StealGuard), along
+/// with a view giving `&mut` access to the other fields of the struct, for
+/// structs that can't derive `Stealable`. The value is returned once the
+/// guard is dropped.
+///
+/// A declarative macro can't see the fields of the struct, so the other
+/// fields are listed after the stolen one, and only those are in the view.
+/// The view is a struct of its own that can't be named, but its fields can
+/// be passed on as is. The struct is given as a place expression, like
+/// `world` or `*self`, which is evaluated once per field.
+///
+/// Used like `let (thing, rest) = split_view!(world, thing; name, items);`.
+///
+/// Panics if the field is already stolen!
+#[macro_export]
+macro_rules! split_view {
+	($object:expr, $stolen:ident; $($field:ident),* $(,)?) => {
+		(
+			$object.$stolen.steal_guard(),
+			{
+				#[allow(non_camel_case_types)]
+				struct Rest<$($field),*> {
+					$($field: $field),*
+				}
+				Rest {
+					$($field: &mut $object.$field),*
+				}
+			},
+		)
+	};
+}
+
+#[cfg(test)]
+mod test {
+	use crate::StealCell;
+
+	struct Physics {
+		steps: usize,
+	}
+
+	struct World<'a, T> {
+		physics: StealCell<Physics>,
+		name: &'a str,
+		items: Vec<T>,
+		frame: usize,
+	}
+
+	fn tick(frame: &mut usize) -> usize {
+		*frame += 1;
+		*frame
+	}
+
+	fn rename<'a>(name: &mut &'a str, to: &'a str) {
+		*name = to;
+	}
+
+	#[test]
+	fn views_the_listed_fields() {
+		let mut world = World {
+			physics: StealCell::new(Physics { steps: 0 }),
+			name: "world",
+			items: vec![1_u8],
+			frame: 0,
+		};
+
+		{
+			let (mut physics, rest) = crate::split_view!(world, physics; name, items, frame,);
+			assert!(physics.get().steps == 0);
+			physics.get_mut().steps = tick(rest.frame);
+			rename(rest.name, "renamed");
+			rest.items.push(2);
+		}
+
+		assert!(!world.physics.is_stolen());
+		assert_eq!(world.physics.as_ref().steps, 1);
+		assert_eq!(world.name, "renamed");
+		assert_eq!(world.items, [1, 2]);
+	}
+
+	#[test]
+	fn works_through_references() {
+		fn step<T>(world: &mut World<'_, T>) {
+			let (mut physics, rest) = crate::split_view!(*world, physics; frame);
+			physics.get_mut().steps += tick(rest.frame);
+		}
+
+		let mut world = World::<()> {
+			physics: StealCell::new(Physics { steps: 0 }),
+			name: "world",
+			items: Vec::new(),
+			frame: 0,
+		};
+		step(&mut world);
+		step(&mut world);
+		assert_eq!(world.physics.as_ref().steps, 3);
+	}
+}
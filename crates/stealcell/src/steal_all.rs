@@ -0,0 +1,283 @@
+use crate::{
+	StealCell, StealEachError, StealError, Stolen, handle_violation, violation::violation,
+};
+
+/// Steals from every cell of a tuple of `&mut StealCell`s at once, of up to 8
+/// cells, either stealing all of them or none.
+pub trait StealAll {
+	/// A tuple of the stolen values, in the order of the cells.
+	type Stolen;
+
+	/// Steals from every cell, which you must promise to return soon, with
+	/// [StealAll::return_all]!
+	///
+	/// Panics if any of them is already stolen, after returning the values
+	/// stolen from the cells before it.
+	#[track_caller]
+	fn steal_all(&mut self) -> Self::Stolen {
+		self.try_steal_all().unwrap_or_else(|error| {
+			handle_violation(|| error.violation());
+			violation!(error.violation(), "{}", error)
+		})
+	}
+
+	/// Like [StealAll::steal_all] but instead of panicking, returns the
+	/// error of the first cell that is already stolen, after returning the
+	/// values stolen from the cells before it.
+	#[must_use = "the stolen values must be returned with return_all or they will panic on drop"]
+	fn try_steal_all(&mut self) -> Result<Self::Stolen, StealError>;
+
+	/// Returns every value to its own cell, checked just like
+	/// [StealCell::return_stolen] does.
+	///
+	/// Panics in the same cases as [StealCell::return_stolen], on the first
+	/// value that can't be returned.
+	fn return_all(&mut self, stolen: Self::Stolen);
+}
+
+macro_rules! impl_steal_all {
+	($($cell:ident: $value:ident => $stolen:ident),+) => {
+		impl<$($value),+> StealAll for ($(&mut StealCell<$value>,)+) {
+			type Stolen = ($(Stolen<$value>,)+);
+
+			#[track_caller]
+			fn try_steal_all(&mut self) -> Result<Self::Stolen, StealError> {
+				let ($($cell,)+) = self;
+				$(let mut $stolen = None;)+
+				let failed = 'steal: {
+					$(match $cell.try_steal() {
+						Ok(stolen) => $stolen = Some(stolen),
+						Err(error) => break 'steal Some(error),
+					})+
+					None
+				};
+				if let Some(error) = failed {
+					$(if let Some(stolen) = $stolen {
+						$cell.return_stolen(stolen);
+					})+
+					return Err(error);
+				}
+				// PANIC SAFETY: Every cell was stolen from, or it returned early.
+				Ok(($($stolen.unwrap(),)+))
+			}
+
+			fn return_all(&mut self, ($($stolen,)+): Self::Stolen) {
+				let ($($cell,)+) = self;
+				$($cell.return_stolen($stolen);)+
+			}
+		}
+	};
+}
+
+/// Steals from every cell of an array of cells at once, either stealing all
+/// of them or none.
+pub trait StealEach {
+	/// An array of the stolen values, in the order of the cells.
+	type Stolen;
+
+	/// Steals from every cell, which you must promise to return soon, with
+	/// [StealEach::return_each]!
+	///
+	/// Panics if any of them is already stolen, after returning the values
+	/// stolen from the cells before it.
+	#[track_caller]
+	fn steal_each(&mut self) -> Self::Stolen {
+		self.try_steal_each().unwrap_or_else(|error| {
+			handle_violation(|| error.error.violation());
+			violation!(error.error.violation(), "{}", error)
+		})
+	}
+
+	/// Like [StealEach::steal_each] but instead of panicking, returns the
+	/// index and the error of the first cell that is already stolen, after
+	/// returning the values stolen from the cells before it.
+	#[must_use = "the stolen values must be returned with return_each or they will panic on drop"]
+	fn try_steal_each(&mut self) -> Result<Self::Stolen, StealEachError>;
+
+	/// Returns every value to its own cell, checked just like
+	/// [StealCell::return_stolen] does.
+	///
+	/// Panics in the same cases as [StealCell::return_stolen], on the first
+	/// value that can't be returned.
+	fn return_each(&mut self, stolen: Self::Stolen);
+}
+
+impl<T, const N: usize> StealEach for [StealCell<T>; N] {
+	type Stolen = [Stolen<T>; N];
+
+	#[track_caller]
+	fn try_steal_each(&mut self) -> Result<Self::Stolen, StealEachError> {
+		let mut stolen: [Option<Stolen<T>>; N] = core::array::from_fn(|_| None);
+		for index in 0..N {
+			match self[index].try_steal() {
+				Ok(value) => stolen[index] = Some(value),
+				Err(error) => {
+					for (cell, stolen) in self.iter_mut().zip(stolen).take(index) {
+						// PANIC SAFETY: Every cell before the index was stolen from.
+						cell.return_stolen(stolen.unwrap());
+					}
+					return Err(StealEachError { index, error });
+				}
+			}
+		}
+		// PANIC SAFETY: Every cell was stolen from, or it returned early.
+		Ok(stolen.map(Option::unwrap))
+	}
+
+	fn return_each(&mut self, stolen: Self::Stolen) {
+		for (cell, stolen) in self.iter_mut().zip(stolen) {
+			cell.return_stolen(stolen);
+		}
+	}
+}
+
+impl_steal_all!(a: A => stolen_a);
+impl_steal_all!(a: A => stolen_a, b: B => stolen_b);
+impl_steal_all!(a: A => stolen_a, b: B => stolen_b, c: C => stolen_c);
+impl_steal_all!(a: A => stolen_a, b: B => stolen_b, c: C => stolen_c, d: D => stolen_d);
+impl_steal_all!(
+	a: A => stolen_a,
+	b: B => stolen_b,
+	c: C => stolen_c,
+	d: D => stolen_d,
+	e: E => stolen_e
+);
+impl_steal_all!(
+	a: A => stolen_a,
+	b: B => stolen_b,
+	c: C => stolen_c,
+	d: D => stolen_d,
+	e: E => stolen_e,
+	f: F => stolen_f
+);
+impl_steal_all!(
+	a: A => stolen_a,
+	b: B => stolen_b,
+	c: C => stolen_c,
+	d: D => stolen_d,
+	e: E => stolen_e,
+	f: F => stolen_f,
+	g: G => stolen_g
+);
+impl_steal_all!(
+	a: A => stolen_a,
+	b: B => stolen_b,
+	c: C => stolen_c,
+	d: D => stolen_d,
+	e: E => stolen_e,
+	f: F => stolen_f,
+	g: G => stolen_g,
+	h: H => stolen_h
+);
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn steals_every_cell() {
+		let mut health = StealCell::new(10_u8);
+		let mut name = StealCell::new("hero");
+		let mut position = StealCell::new((1.0_f32, 2.0_f32));
+
+		let (mut h, n, mut p) = (&mut health, &mut name, &mut position).steal_all();
+		assert!(health.is_stolen() && name.is_stolen() && position.is_stolen());
+		*h.get_mut() -= 1;
+		p.get_mut().0 += 1.0;
+		assert_eq!(*n.get(), "hero");
+		(&mut health, &mut name, &mut position).return_all((h, n, p));
+
+		assert_eq!(health, 9);
+		assert_eq!(name, "hero");
+		assert_eq!(position, (2.0, 2.0));
+	}
+
+	#[test]
+	fn rolls_back_when_a_cell_is_stolen() {
+		let mut health = StealCell::new(10_u8);
+		let mut name = StealCell::new("hero");
+		let mut position = StealCell::new((1.0_f32, 2.0_f32));
+		let stolen_position = position.steal();
+
+		let error = (&mut health, &mut name, &mut position)
+			.try_steal_all()
+			.err()
+			.unwrap();
+		assert!(matches!(error, StealError::AlreadyStolen { .. }));
+		assert!(!health.is_stolen());
+		assert!(!name.is_stolen());
+		position.return_stolen(stolen_position);
+
+		let (h, n, p) = (&mut health, &mut name, &mut position).steal_all();
+		(&mut health, &mut name, &mut position).return_all((h, n, p));
+	}
+
+	#[test]
+	#[should_panic(expected = "already stolen")]
+	#[cfg(not(feature = "typed_panics"))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_when_a_cell_is_stolen() {
+		let mut health = StealCell::new(10_u8);
+		let mut name = StealCell::new("hero");
+		name.steal().discard();
+		let _ = (&mut health, &mut name).steal_all();
+	}
+
+	/// The other value is lost while unwinding, which aborts with `no_std`.
+	#[test]
+	#[cfg(not(feature = "no_std"))]
+	#[should_panic(expected = "different cell")]
+	#[cfg(not(feature = "typed_panics"))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn checks_every_return() {
+		let mut a = StealCell::new(1);
+		let mut b = StealCell::new(2);
+		let (stolen_a, stolen_b) = (&mut a, &mut b).steal_all();
+		(&mut a, &mut b).return_all((stolen_b, stolen_a));
+	}
+
+	#[test]
+	fn steals_each_cell() {
+		let mut channels = [StealCell::new(1), StealCell::new(2), StealCell::new(3)];
+		let mut stolen = channels.steal_each();
+		assert!(channels.iter().all(StealCell::is_stolen));
+		for channel in &mut stolen {
+			*channel.get_mut() *= 10;
+		}
+		channels.return_each(stolen);
+		assert_eq!(channels, [10, 20, 30]);
+	}
+
+	#[test]
+	fn rolls_back_when_an_element_is_stolen() {
+		let mut channels: [StealCell<usize>; 8] = core::array::from_fn(StealCell::new);
+		let stolen_channel = channels[5].steal();
+
+		let error = channels.try_steal_each().err().unwrap();
+		assert_eq!(error.index, 5);
+		assert!(matches!(error.error, StealError::AlreadyStolen { .. }));
+		assert!(
+			error
+				.to_string()
+				.starts_with("can't steal cell 5, value already stolen")
+		);
+		assert!(channels[..5].iter().all(|channel| !channel.is_stolen()));
+		assert!(channels[6..].iter().all(|channel| !channel.is_stolen()));
+		assert_eq!(channels[..5], [0, 1, 2, 3, 4]);
+		channels[5].return_stolen(stolen_channel);
+
+		let stolen = channels.steal_each();
+		channels.return_each(stolen);
+	}
+
+	#[test]
+	#[should_panic(expected = "can't steal cell 1")]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_when_an_element_is_stolen() {
+		let mut channels = [StealCell::new(1), StealCell::new(2)];
+		channels[1].steal().discard();
+		let _ = channels.steal_each();
+	}
+}
@@ -0,0 +1,198 @@
+#[cfg(not(feature = "no_std"))]
+use core::ops::{Deref, DerefMut};
+
+use crate::{StealCell, Stolen};
+
+/// A value stolen from a [StealCell] that is returned once the guard is
+/// dropped, even while unwinding from a panic, see [StealCell::steal_guard].
+#[must_use = "the value is returned right away if the guard is not used"]
+pub struct StealGuard<'a, T> {
+	pub(crate) cell: &'a mut StealCell<T>,
+	/// Only `None` once returned.
+	pub(crate) stolen: Option<Stolen<T>>,
+}
+
+impl<T> StealGuard<'_, T> {
+	/// Returns a reference to the stolen value. Unlike `Deref`, this is also
+	/// available with `no_std`.
+	pub fn get(&self) -> &T {
+		// PANIC SAFETY: Only taken when the guard is consumed.
+		self.stolen.as_ref().unwrap().get()
+	}
+
+	/// Returns a mutable reference to the stolen value. Unlike `DerefMut`,
+	/// this is also available with `no_std`.
+	pub fn get_mut(&mut self) -> &mut T {
+		self.stolen_mut().get_mut()
+	}
+
+	/// Cancels returning the value, handing it out as a regular, armed
+	/// stolen value instead, to be returned by hand. The cell stays stolen.
+	pub fn defuse(mut self) -> Stolen<T> {
+		// PANIC SAFETY: Only taken when the guard is consumed.
+		self.stolen.take().unwrap()
+	}
+
+	/// The same as [StealGuard::defuse].
+	pub fn into_stolen(self) -> Stolen<T> {
+		self.defuse()
+	}
+
+	pub(crate) fn stolen_mut(&mut self) -> &mut Stolen<T> {
+		// PANIC SAFETY: Only taken when the guard is consumed.
+		self.stolen.as_mut().unwrap()
+	}
+
+	/// Returns the value right away, panicking as usual if it was swapped
+	/// for one that can't be returned to the cell.
+	pub(crate) fn finish(mut self) {
+		if let Some(stolen) = self.stolen.take() {
+			self.cell.return_stolen(stolen);
+		}
+	}
+}
+
+impl<T> Drop for StealGuard<'_, T> {
+	/// Only [StealCell::with_stolen] can swap the stolen value, and it uses
+	/// [StealGuard::finish] unless unwinding, so failing to return the value
+	/// here only loses it like any other stolen value dropped while
+	/// panicking.
+	fn drop(&mut self) {
+		if let Some(stolen) = self.stolen.take() {
+			let _ = self.cell.try_return_stolen(stolen);
+		}
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> Deref for StealGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		self.get()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> DerefMut for StealGuard<'_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.get_mut()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::StealCell;
+
+	struct World {
+		physics: StealCell<usize>,
+		audio: StealCell<usize>,
+	}
+
+	#[test]
+	fn returns_the_value_when_dropped() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		{
+			let mut guard = stealcell.steal_guard();
+			*guard.get_mut() += 1;
+			assert_eq!(*guard.get(), 2);
+		}
+		assert!(!stealcell.is_stolen());
+		assert_eq!(stealcell, 2);
+	}
+
+	#[test]
+	fn returns_the_value_on_early_returns() {
+		fn parse_into(stealcell: &mut StealCell<usize>, text: &str) -> Result<(), ()> {
+			let mut guard = stealcell.steal_guard();
+			*guard.get_mut() = text.parse().map_err(|_| ())?;
+			Ok(())
+		}
+
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(parse_into(&mut stealcell, "nope"), Err(()));
+		assert_eq!(stealcell, 1);
+		assert_eq!(parse_into(&mut stealcell, "12"), Ok(()));
+		assert_eq!(stealcell, 12);
+	}
+
+	#[test]
+	fn returns_the_value_when_panicking() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(|_| {}));
+		let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+			let mut guard = stealcell.steal_guard();
+			*guard.get_mut() += 1;
+			panic!("the mod callback failed");
+		}));
+		std::panic::set_hook(hook);
+
+		assert!(result.is_err());
+		assert!(!stealcell.is_stolen());
+		assert_eq!(stealcell, 2);
+	}
+
+	#[test]
+	#[cfg(not(feature = "no_std"))]
+	fn derefs() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let mut guard = stealcell.steal_guard();
+		*guard += 1;
+		assert_eq!(*guard, 2);
+	}
+
+	#[test]
+	fn defusing_leaves_the_cell_stolen() {
+		let mut stealcell = StealCell::<usize>::new_named(1, "score");
+		let mut guard = stealcell.steal_guard();
+		*guard.get_mut() += 1;
+		let stolen = guard.defuse();
+		assert!(stealcell.is_stolen());
+		assert!(stealcell.try_steal().is_err());
+
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		assert_eq!(stolen.label(), Some("score"));
+		let stolen = stolen.map(|value| value * 10);
+		stealcell.return_stolen_unchecked(stolen);
+		assert_eq!(stealcell, 20);
+
+		let stolen = stealcell.steal_guard().into_stolen();
+		assert!(stealcell.is_stolen());
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell, 20);
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, not(feature = "release_disarm")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn defused_values_are_armed() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal_guard().defuse();
+		let hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(|_| {}));
+		let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(stolen)));
+		std::panic::set_hook(hook);
+		assert!(result.is_err());
+	}
+
+	#[test]
+	fn nests() {
+		let mut world = World {
+			physics: StealCell::new(1),
+			audio: StealCell::new(10),
+		};
+		{
+			let mut physics = world.physics.steal_guard();
+			{
+				let mut audio = world.audio.steal_guard();
+				*audio.get_mut() += *physics.get();
+				*physics.get_mut() += *audio.get();
+			}
+			assert_eq!(*physics.get(), 12);
+			assert!(!world.audio.is_stolen());
+		}
+		assert_eq!(world.physics, 12);
+		assert_eq!(world.audio, 11);
+	}
+}
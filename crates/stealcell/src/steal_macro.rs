@@ -0,0 +1,247 @@
+use crate::{StealCell, StealError, Stolen};
+
+/// Steals from several cells for the duration of a body that can still use
+/// whatever the cells live in, returning every value afterwards, even if
+/// the body panics, in which case the panic carries on once they are back.
+///
+/// Inside the body, each binding is a `&mut` to its stolen value. The body
+/// runs in a closure, so `return` and `?` leave the body, not the enclosing
+/// function, after which the values are returned and the macro evaluates to
+/// whatever the body returned. Cells are given as place expressions, like
+/// `world.physics`, which are evaluated again to return the values.
+///
+/// With `no_std`, a panicking body loses the values.
+///
+/// Used like `steal!(world.physics => physics, world.audio => audio; { .. })`,
+/// with up to 8 cells, stealing from all of them or none, like
+/// [StealAll::steal_all](crate::StealAll::steal_all).
+///
+/// Panics if any of the cells is already stolen, after returning the values
+/// stolen from the cells before it, or if a value can't be returned, like
+/// when the body refilled its cell, after returning the others!
+#[macro_export]
+macro_rules! steal {
+	($($cell:expr => $binding:ident),+ $(,)?; $body:block) => {{
+		let ($(mut $binding,)+) = $crate::StealAll::steal_all(&mut ($(&mut $cell,)+));
+		let caught = $crate::__catch_body(|| {
+			$(let $binding = $binding.get_mut();)+
+			$body
+		});
+		$(let caught = $crate::__return_after(caught, || $cell.return_stolen($binding));)+
+		$crate::__resume_body(caught)
+	}};
+}
+
+/// The result of the body of [steal], or the payload it panicked with.
+#[doc(hidden)]
+#[cfg(not(feature = "no_std"))]
+pub type __Caught<R> = Result<R, std::boxed::Box<dyn core::any::Any + Send>>;
+
+/// Panics can't be caught without std, but a type that can't be empty keeps
+/// diverging bodies from making the code returning the values unreachable.
+#[doc(hidden)]
+#[cfg(feature = "no_std")]
+pub type __Caught<R> = Option<R>;
+
+/// Runs the body of [steal], catching its panic to return the values first.
+#[doc(hidden)]
+pub fn __catch_body<R>(body: impl FnOnce() -> R) -> __Caught<R> {
+	#[cfg(not(feature = "no_std"))]
+	return std::panic::catch_unwind(core::panic::AssertUnwindSafe(body));
+	#[cfg(feature = "no_std")]
+	Some(body())
+}
+
+/// Returns a value stolen by [steal] even if the body or an earlier return
+/// panicked, keeping the first panic to carry on with.
+#[doc(hidden)]
+pub fn __return_after<R>(caught: __Caught<R>, return_stolen: impl FnOnce()) -> __Caught<R> {
+	#[cfg(not(feature = "no_std"))]
+	{
+		let returned = std::panic::catch_unwind(core::panic::AssertUnwindSafe(return_stolen));
+		caught.and_then(|result| returned.map(|()| result))
+	}
+	#[cfg(feature = "no_std")]
+	{
+		return_stolen();
+		caught
+	}
+}
+
+/// Hands over the result of the body of [steal], or carries on with its
+/// panic.
+#[doc(hidden)]
+pub fn __resume_body<R>(caught: __Caught<R>) -> R {
+	#[cfg(not(feature = "no_std"))]
+	return caught.unwrap_or_else(|payload| std::panic::resume_unwind(payload));
+	// PANIC SAFETY: Always `Some`, see [__Caught].
+	#[cfg(feature = "no_std")]
+	caught.unwrap()
+}
+
+/// The fields `#[with_stolen]` steals from, so that it can tell when one
+/// isn't a [StealCell].
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` is not a `StealCell`",
+	label = "`#[with_stolen]` can only steal from `StealCell` fields"
+)]
+pub trait __StealCellField {
+	type Value;
+
+	fn __try_steal(&mut self) -> Result<Stolen<Self::Value>, StealError>;
+
+	fn __fail_steal(&self, error: StealError) -> !;
+
+	fn __return(&mut self, stolen: Stolen<Self::Value>);
+}
+
+impl<T> __StealCellField for StealCell<T> {
+	type Value = T;
+
+	#[track_caller]
+	fn __try_steal(&mut self) -> Result<Stolen<T>, StealError> {
+		self.try_steal()
+	}
+
+	#[track_caller]
+	fn __fail_steal(&self, error: StealError) -> ! {
+		Self::fail_steal(error)
+	}
+
+	fn __return(&mut self, stolen: Stolen<T>) {
+		self.return_stolen(stolen);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::StealCell;
+
+	struct World {
+		physics: StealCell<usize>,
+		audio: StealCell<usize>,
+		frame: usize,
+	}
+
+	impl World {
+		fn new() -> Self {
+			Self {
+				physics: StealCell::new(1),
+				audio: StealCell::new(10),
+				frame: 0,
+			}
+		}
+	}
+
+	#[test]
+	fn returns_every_value() {
+		let mut world = World::new();
+		let sum = crate::steal!(world.physics => physics, world.audio => audio; {
+			assert!(world.physics.is_stolen());
+			world.frame += 1;
+			*physics += world.frame;
+			*audio += *physics;
+			*physics + *audio
+		});
+		assert_eq!(sum, 14);
+		assert_eq!(world.physics, 2);
+		assert_eq!(world.audio, 12);
+	}
+
+	#[test]
+	fn returns_every_value_on_early_returns() {
+		fn step(world: &mut World, skip: bool) -> Result<usize, &'static str> {
+			let frame = crate::steal!(world.physics => physics, world.audio => audio,; {
+				if skip {
+					return Err("skipped");
+				}
+				*physics += 1;
+				*audio += 1;
+				world.frame += 1;
+				Ok(world.frame)
+			})?;
+			Ok(frame * 10)
+		}
+
+		let mut world = World::new();
+		assert_eq!(step(&mut world, true), Err("skipped"));
+		assert!(!world.physics.is_stolen());
+		assert!(!world.audio.is_stolen());
+		assert_eq!(step(&mut world, false), Ok(10));
+		assert_eq!(world.physics, 2);
+		assert_eq!(world.audio, 11);
+	}
+
+	/// Runs the function expecting it to panic, returning its payload.
+	#[cfg(not(feature = "no_std"))]
+	fn panic_payload(fun: impl FnOnce()) -> Box<dyn core::any::Any + Send> {
+		let hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(|_| {}));
+		let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(fun));
+		std::panic::set_hook(hook);
+		result.expect_err("expected a panic")
+	}
+
+	#[test]
+	#[cfg(not(feature = "no_std"))]
+	fn returns_every_value_when_panicking() {
+		let mut world = World::new();
+		let payload = panic_payload(|| {
+			crate::steal!(world.physics => physics; {
+				*physics += 1;
+				panic!("the physics step failed");
+			})
+		});
+		assert_eq!(
+			payload.downcast_ref::<&str>(),
+			Some(&"the physics step failed")
+		);
+		assert!(!world.physics.is_stolen());
+		assert_eq!(world.physics, 2);
+	}
+
+	#[test]
+	#[cfg(not(feature = "no_std"))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn returns_earlier_values_when_a_cell_is_stolen() {
+		let mut world = World::new();
+		let audio = world.audio.steal();
+		panic_payload(|| {
+			crate::steal!(world.physics => physics, world.audio => audio; {
+				*physics += *audio;
+			})
+		});
+		assert!(!world.physics.is_stolen());
+		assert_eq!(world.physics, 1);
+		world.audio.return_stolen(audio);
+	}
+
+	#[test]
+	#[cfg(not(feature = "no_std"))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn returns_later_values_when_a_value_can_not_be_returned() {
+		let mut world = World::new();
+		panic_payload(|| {
+			crate::steal!(world.physics => physics, world.audio => audio; {
+				*physics += 1;
+				*audio += 1;
+				world.physics.set(5).unwrap();
+			})
+		});
+		assert_eq!(world.physics, 5);
+		assert!(!world.audio.is_stolen());
+		assert_eq!(world.audio, 11);
+	}
+
+	#[test]
+	fn bindings_do_not_leak_out() {
+		let caught = 1;
+		let mut world = World::new();
+		crate::steal!(world.physics => physics; {
+			*physics += caught;
+		});
+		assert_eq!(caught, 1);
+		assert_eq!(world.physics, 2);
+	}
+}
@@ -1,9 +1,73 @@
-use core::any::type_name;
+use core::{
+	borrow::{Borrow, BorrowMut},
+	convert::Infallible,
+	fmt::{self, Debug, Display, Formatter},
+	panic::{Location, RefUnwindSafe, UnwindSafe},
+	time::Duration,
+};
 
-#[cfg(not(feature = "no_std"))]
-use std::ops::{Deref, DerefMut};
+#[cfg(any(target_has_atomic = "ptr", feature = "portable_atomic"))]
+use crate::MustReturn;
+use crate::{
+	CellState, ReturnError, ShortTypeName, StealError, StealGuard, StealPlaceholder,
+	StealViolation, SwapError, TransferError, ViolationKind, handle_violation, value_type_name,
+	violation::{misuse, violation},
+};
+
+use core::{
+	num::NonZeroUsize,
+	ops::{Deref, DerefMut},
+};
+
+#[cfg(feature = "poison")]
+use alloc::sync::Arc;
+#[cfg(feature = "poison")]
+use core::sync::atomic::AtomicBool;
+#[cfg(all(target_has_atomic = "ptr", not(feature = "portable_atomic")))]
+use core::sync::atomic::AtomicUsize;
+#[cfg(any(
+	feature = "poison",
+	target_has_atomic = "ptr",
+	feature = "portable_atomic"
+))]
+use core::sync::atomic::Ordering;
+#[cfg(feature = "portable_atomic")]
+use portable_atomic::AtomicUsize;
+#[cfg(feature = "steal_timing")]
+use std::time::Instant;
+
+/// Called with a value that was lost instead of dropping it, see
+/// [StealCell::on_loss].
+#[cfg(feature = "salvage")]
+type SalvageHook<T> = std::sync::Arc<std::sync::Mutex<std::boxed::Box<dyn FnMut(T) + Send>>>;
+
+/// Poisons the cell while its value is out, unless disarmed by taking the
+/// flag back, see [StealCell::try_map_in_place].
+#[cfg(feature = "poison")]
+struct PoisonOnUnwind(Option<Arc<AtomicBool>>);
+
+#[cfg(feature = "poison")]
+impl Drop for PoisonOnUnwind {
+	fn drop(&mut self) {
+		if let Some(poison) = self.0.take() {
+			poison.store(true, Ordering::Release);
+		}
+	}
+}
 
-const ALREADY_STOLEN: &str = "value already stolen from:";
+/// Cells get their id assigned on their first steal, cells that are never
+/// stolen from don't need one. Targets without compare-and-swap have no ids
+/// unless using the `portable_atomic` feature, so values stolen from one cell
+/// can be returned to another.
+#[cfg(any(target_has_atomic = "ptr", feature = "portable_atomic"))]
+static NEXT_CELL_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Assigns an id to a cell, shared with [DepositCell](crate::DepositCell)
+/// so that ids are unique across both.
+#[cfg(any(target_has_atomic = "ptr", feature = "portable_atomic"))]
+pub(crate) fn next_cell_id() -> NonZeroUsize {
+	NonZeroUsize::new(NEXT_CELL_ID.fetch_add(1, Ordering::Relaxed)).expect("ran out of cell ids")
+}
 
 /// An Option like type that lets you temporarily remove a value from somewhere
 /// to retain mutable access on both.
@@ -15,9 +79,104 @@ const ALREADY_STOLEN: &str = "value already stolen from:";
 /// The `Default` implementation defaults to `Some(T)` if `T` is `Default`,
 /// as the base assumption of StealCell that there is something in it, unless
 /// it was explicitly stolen.
-#[derive(PartialEq, Eq, Debug)]
+///
+/// Stolen values remember which cell they were stolen from, and can only be
+/// returned there, see [StealCell::return_stolen_unchecked] to opt out. Once
+/// the cell is refilled, values stolen before that are stale and can't be
+/// returned either. Targets without compare-and-swap can't tell cells apart,
+/// unless using the `portable_atomic` feature.
+///
+/// Cells only keep track of this in debug builds or with the `diagnostics`
+/// feature, along with their label and where they were last stolen, so that
+/// in release builds they are no bigger than an [Option].
 pub struct StealCell<T> {
 	value: Option<T>,
+	trace: Trace,
+	/// Shared with the outstanding stolen value, which raises it when lost.
+	#[cfg(feature = "poison")]
+	poison: Option<Arc<AtomicBool>>,
+	/// How long values may stay stolen, see [StealCell::set_steal_deadline].
+	#[cfg(feature = "deadline")]
+	deadline: Option<Duration>,
+	/// When the value was last stolen, to check the deadline.
+	#[cfg(feature = "deadline")]
+	stolen_since: Option<Instant>,
+	/// How many times the value was stolen, see [StealCell::steal_count].
+	#[cfg(feature = "stats")]
+	steals: u64,
+	/// How many times the value was returned, see [StealCell::return_count].
+	#[cfg(feature = "stats")]
+	returns: u64,
+	/// Registers the outstanding steals of the cell, see [StealCell::tracked].
+	#[cfg(feature = "test_util")]
+	tracking: Option<crate::registry::Tracking>,
+	/// Handed to stolen values to be called if they are lost, see
+	/// [StealCell::on_loss].
+	#[cfg(feature = "salvage")]
+	salvage: Option<SalvageHook<T>>,
+}
+
+/// A stolen cell is never equal to anything, not even to another stolen
+/// cell, which is also why there is no `Eq`. See [StealCell::option_eq] to
+/// compare them like options instead.
+impl<T> PartialEq for StealCell<T>
+where
+	T: PartialEq,
+{
+	fn eq(&self, other: &Self) -> bool {
+		self.value_eq(other)
+	}
+}
+
+// Spelled out so that they don't depend on what the cell keeps besides the
+// value, the bookkeeping is never left in a broken state by a panic.
+impl<T> UnwindSafe for StealCell<T> where T: UnwindSafe {}
+impl<T> RefUnwindSafe for StealCell<T> where T: RefUnwindSafe {}
+
+/// What a stolen cell prints instead of its value, with both `{}` and
+/// `{:?}`.
+pub const STOLEN_MARKER: &str = "<stolen>";
+
+/// Prints `StealCell(<value>)`, or `StealCell(<stolen>)` when stolen. The
+/// alternate `{:#?}` form also prints the label and where the value was
+/// stolen, when known.
+impl<T> Debug for StealCell<T>
+where
+	T: Debug,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let stolen = format_args!("{STOLEN_MARKER}");
+		let value: &dyn Debug = match &self.value {
+			Some(value) => value,
+			None => &stolen,
+		};
+		if !f.alternate() {
+			return f.debug_tuple("StealCell").field(value).finish();
+		}
+
+		let mut debug = f.debug_struct("StealCell");
+		debug.field("value", value);
+		if let Some(label) = self.trace.label() {
+			debug.field("label", &label);
+		}
+		if let Some(stolen_at) = self.trace.stolen_at() {
+			debug.field("stolen_at", &format_args!("{stolen_at}"));
+		}
+		debug.finish()
+	}
+}
+
+/// Prints the value, or [STOLEN_MARKER] when stolen.
+impl<T> Display for StealCell<T>
+where
+	T: Display,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match &self.value {
+			Some(value) => value.fmt(f),
+			None => f.write_str(STOLEN_MARKER),
+		}
+	}
 }
 
 impl<T> Default for StealCell<T>
@@ -25,240 +184,3804 @@ where
 	T: Default,
 {
 	fn default() -> Self {
-		Self {
-			value: Some(T::default()),
-		}
+		Self::new(T::default())
 	}
 }
 
 impl<T> StealCell<T> {
 	pub fn new(value: T) -> Self {
-		Self { value: Some(value) }
+		Self::from(Some(value))
+	}
+
+	/// Creates a cell that starts out in the stolen state, to be filled
+	/// later using [StealCell::set].
+	///
+	/// Reading from it panics just like with any other stolen cell!
+	pub fn empty() -> Self {
+		Self::from(None)
+	}
+
+	/// Creates a cell with a label that is included in every panic and error
+	/// message about it, and of the values stolen from it, to tell apart
+	/// cells of the same type. Only kept in debug builds or with the
+	/// `diagnostics` feature.
+	pub fn new_named(value: T, name: &'static str) -> Self {
+		let mut stealcell = Self::new(value);
+		stealcell.set_label(name);
+		stealcell
+	}
+
+	/// Creates a cell that registers its stolen values until they are
+	/// returned, so that [assert_all_returned](crate::assert_all_returned)
+	/// can list the ones that never were. The cell unregisters once dropped.
+	#[cfg(feature = "test_util")]
+	pub fn tracked(value: T) -> Self {
+		let mut stealcell = Self::new(value);
+		stealcell.tracking = Some(crate::registry::Tracking(next_cell_id()));
+		stealcell
+	}
+
+	/// Labels the cell, see [StealCell::new_named]. Values already stolen
+	/// keep the label they were stolen with.
+	pub fn set_label(&mut self, label: &'static str) {
+		self.trace.set_label(label);
+	}
+
+	/// Returns the label of the cell, if it has one and it's kept, see
+	/// [StealCell::new_named].
+	pub fn label(&self) -> Option<&'static str> {
+		self.trace.label()
+	}
+
+	/// Lets `f` salvage values stolen from this cell that are lost, like an
+	/// unflushed buffer, instead of dropping them. Losing the value still
+	/// panics afterwards, or reaches the violation handler, as usual.
+	///
+	/// Values already stolen keep the hook they were stolen with, and values
+	/// mapped into another type are dropped as before.
+	#[cfg(feature = "salvage")]
+	pub fn on_loss(&mut self, f: impl FnMut(T) + Send + 'static) {
+		self.salvage = Some(std::sync::Arc::new(std::sync::Mutex::new(
+			std::boxed::Box::new(f),
+		)));
+	}
+
+	/// Sets how long values may stay stolen from this cell. Values returned
+	/// later than that are reported to the handler installed with
+	/// [set_deadline_handler](crate::set_deadline_handler), and so are values
+	/// still out when calling [StealCell::check_deadline].
+	#[cfg(feature = "deadline")]
+	pub fn set_steal_deadline(&mut self, deadline: Duration) {
+		self.deadline = Some(deadline);
+	}
+
+	/// Removes the deadline set with [StealCell::set_steal_deadline].
+	#[cfg(feature = "deadline")]
+	pub fn clear_steal_deadline(&mut self) {
+		self.deadline = None;
+	}
+
+	/// Reports the stolen value if it's out for longer than the deadline,
+	/// returning `true` if it was reported. Meant to be called periodically,
+	/// like once every frame, to catch values that are never returned.
+	#[cfg(feature = "deadline")]
+	pub fn check_deadline(&self) -> bool {
+		self.is_stolen() && self.report_overdue(self.stolen_since)
+	}
+
+	/// How many times the value was stolen since the cell was created, or
+	/// since [StealCell::reset_counts].
+	#[cfg(feature = "stats")]
+	pub fn steal_count(&self) -> u64 {
+		self.steals
+	}
+
+	/// How many times a stolen value was returned since the cell was created,
+	/// or since [StealCell::reset_counts].
+	#[cfg(feature = "stats")]
+	pub fn return_count(&self) -> u64 {
+		self.returns
+	}
+
+	/// Sets both [StealCell::steal_count] and [StealCell::return_count] back
+	/// to zero, like at the start of every frame.
+	#[cfg(feature = "stats")]
+	pub fn reset_counts(&mut self) {
+		self.steals = 0;
+		self.returns = 0;
+	}
+
+	/// Reports to the deadline handler if the value stolen at `since` is
+	/// overdue.
+	#[cfg(feature = "deadline")]
+	fn report_overdue(&self, since: Option<Instant>) -> bool {
+		let (Some(deadline), Some(since)) = (self.deadline, since) else {
+			return false;
+		};
+		let elapsed = since.elapsed();
+		if elapsed <= deadline {
+			return false;
+		}
+		crate::deadline::report_overdue(&crate::OverdueSteal {
+			elapsed,
+			deadline,
+			type_name: value_type_name::<T>(),
+			label: self.trace.label(),
+			stolen_at: self.trace.stolen_at(),
+		});
+		true
 	}
 
 	/// Puts the cell into a "stolen" state and returns the stolen value
 	/// which you must promise to return soon!
 	///
-	/// Panics if already stolen!
+	/// Panics if already stolen, reporting where it was stolen first!
+	#[track_caller]
 	pub fn steal(&mut self) -> Stolen<T> {
-		let value = self
-			.value
-			.take()
-			.unwrap_or_else(|| panic!("{ALREADY_STOLEN} {}", type_name::<Self>()));
-		Stolen { value: Some(value) }
+		self.try_steal()
+			.unwrap_or_else(|error| Self::fail_steal(error))
 	}
 
-	pub fn is_stolen(&self) -> bool {
-		self.value.is_none()
+	/// Only steals the value if `predicate` returns `true` for it. Returns
+	/// `None` if the predicate doesn't hold, or if the value is already
+	/// stolen, in which case `predicate` is not called.
+	///
+	/// Just like with [StealCell::steal], the stolen value must be returned!
+	#[must_use = "the stolen value must be returned with return_stolen or it will panic on drop"]
+	#[track_caller]
+	pub fn steal_if(&mut self, predicate: impl FnOnce(&T) -> bool) -> Option<Stolen<T>> {
+		let value = self.value.take_if(|value| predicate(value))?;
+		Some(self.stolen(value))
 	}
 
-	/// Panics if wasn't stolen, or if the returned value was already
-	/// consumed!
-	pub fn return_stolen(&mut self, mut stolen: Stolen<T>) {
-		// In case we'd need to panic, the value is taken first so that
-		// the stolen struct dropping doesn't cause another extra panic.
-		let taken_back = stolen.value.take();
+	/// Steals the value, leaving `placeholder` in its place until it's
+	/// returned, so that the cell never looks stolen to anything reading it
+	/// meanwhile. Returning the value drops the placeholder, see
+	/// [StealCell::return_and_take_placeholder] to get it back instead.
+	///
+	/// Whatever is in the cell when the value is returned is taken for the
+	/// placeholder, even if it was modified. Once the placeholder is swapped
+	/// for another value, like with [StealCell::replace], the stolen value
+	/// is stale. Stealing from the cell meanwhile steals the placeholder.
+	///
+	/// Panics if already stolen!
+	#[track_caller]
+	pub fn steal_replace(&mut self, placeholder: T) -> Stolen<T> {
+		let mut stolen = self.steal();
+		if let Some(origin) = &mut stolen.origin {
+			origin.placeholder = true;
+		}
+		self.value = Some(placeholder);
+		stolen
+	}
 
-		assert!(
-			self.value.is_none(),
-			"trying to return a stolen value, but this cell is not empty! {}",
-			type_name::<Self>()
-		);
+	/// Like [StealCell::steal] but also hands out a [MustReturn] proof, which
+	/// can only be consumed by returning the value to this cell with
+	/// [StealCell::return_stolen_with_proof].
+	///
+	/// Panics if already stolen!
+	#[must_use = "the stolen value and the proof must be handed to return_stolen_with_proof"]
+	#[track_caller]
+	#[cfg(any(target_has_atomic = "ptr", feature = "portable_atomic"))]
+	pub fn steal_with_proof(&mut self) -> (Stolen<T>, MustReturn) {
+		let stolen = self.steal();
+		let proof = MustReturn {
+			cell: self.trace.id(),
+			type_name: value_type_name::<T>(),
+			label: self.trace.label(),
+			stolen_at: stolen.stolen_at(),
+		};
+		(stolen, proof)
+	}
 
-		assert!(
-			taken_back.is_some(),
-			"trying to return a stolen value, but it was already returned! {}",
-			type_name::<Self>()
-		);
-		self.value = Some(taken_back.unwrap());
+	/// Like [StealCell::steal] but the panic message is prefixed with `msg`,
+	/// to tell apart which cell it was.
+	#[track_caller]
+	pub fn expect_steal(&mut self, msg: &str) -> Stolen<T> {
+		self.try_steal().unwrap_or_else(|error| {
+			Self::report_failed_steal(&error);
+			violation!(error.violation(), "{}: {}", msg, error)
+		})
 	}
-}
 
-impl<T> AsRef<T> for StealCell<T> {
-	/// Panics if stolen!
-	fn as_ref(&self) -> &T {
-		self.value
-			.as_ref()
-			.unwrap_or_else(|| panic!("{ALREADY_STOLEN} {}", type_name::<Self>()))
+	/// Like [StealCell::steal] but instead of panicking, returns an error
+	/// if the value was already stolen.
+	#[must_use = "the stolen value must be returned with return_stolen or it will panic on drop"]
+	#[track_caller]
+	pub fn try_steal(&mut self) -> Result<Stolen<T>, StealError> {
+		let value = self.value.take().ok_or_else(|| self.already_stolen())?;
+		Ok(self.stolen(value))
 	}
-}
 
-impl<T> AsMut<T> for StealCell<T> {
-	/// Panics if stolen!
-	fn as_mut(&mut self) -> &mut T {
-		self.value
-			.as_mut()
-			.unwrap_or_else(|| panic!("{ALREADY_STOLEN} {}", type_name::<Self>()))
+	/// Like [StealCell::try_steal], but for stealing on behalf of a caller
+	/// that isn't on the stack anymore, like that of a future.
+	#[cfg(not(feature = "no_std"))]
+	pub(crate) fn try_steal_at(
+		&mut self,
+		stolen_at: &'static Location<'static>,
+	) -> Result<Stolen<T>, StealError> {
+		let value = self.value.take().ok_or_else(|| self.already_stolen())?;
+		Ok(self.stolen_at(value, stolen_at))
 	}
-}
 
-#[cfg(not(feature = "no_std"))]
-impl<T> Deref for StealCell<T> {
-	type Target = T;
+	/// Steals the value for the duration of `f`, and returns it whether `f`
+	/// returns or panics, handing back the panic payload in the latter case.
+	/// Either way, the cell is occupied again afterwards.
+	///
+	/// Note that a panicking `f` may leave the value half modified, this is
+	/// why `&mut T` is not [UnwindSafe] on its own. Not available with
+	/// `no_std`.
+	///
+	/// Panics if already stolen!
+	#[cfg(not(feature = "no_std"))]
+	#[track_caller]
+	pub fn steal_catch<R>(
+		&mut self,
+		f: impl FnOnce(&mut T) -> R + UnwindSafe,
+	) -> Result<R, std::boxed::Box<dyn core::any::Any + Send>> {
+		let mut stolen = self.steal();
+		// `f` is unwind safe, only the value is asserted, as documented.
+		let result =
+			std::panic::catch_unwind(core::panic::AssertUnwindSafe(|| f(stolen.get_mut())));
+		self.return_stolen(stolen);
+		result
+	}
 
-	/// PANIC SAFETY: The stored value can only be `None` by giving ownership
-	/// away and returning it to the `StealCell`.
-	fn deref(&self) -> &Self::Target {
-		self.as_ref()
+	/// Steals the value for the duration of `f`, and returns it right after,
+	/// even if `f` panics, in which case the panic carries on once the value
+	/// is back. Either way, the cell is occupied again afterwards.
+	///
+	/// Panics if already stolen, or if `f` swaps the stolen value for one
+	/// that can't be returned here!
+	#[track_caller]
+	pub fn with_stolen<R>(&mut self, f: impl FnOnce(&mut Stolen<T>) -> R) -> R {
+		let mut guard = self.steal_guard();
+		let result = f(guard.stolen_mut());
+		guard.finish();
+		result
 	}
-}
 
-#[cfg(not(feature = "no_std"))]
-impl<T> DerefMut for StealCell<T> {
-	/// PANIC SAFETY: The stored value can only be `None` by giving ownership
-	/// away and returning it to the `StealCell`.
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		self.as_mut()
+	/// Steals the value into a guard that returns it to the cell once
+	/// dropped, even while unwinding from a panic. The guard borrows the
+	/// cell, so unlike [StealCell::steal], the cell can't be used meanwhile.
+	///
+	/// Panics if already stolen!
+	#[track_caller]
+	pub fn steal_guard(&mut self) -> StealGuard<'_, T> {
+		StealGuard {
+			stolen: Some(self.steal()),
+			cell: self,
+		}
 	}
-}
 
-/// A value stolen from a [StealCell]. If you accidentally drop it before
-/// returning it where it belongs, it will panic!
-pub struct Stolen<T> {
-	/// Starts out as Some, becomes None once returned.
-	/// If not returned, panics!
-	value: Option<T>,
-}
+	/// Like [StealCell::with_stolen] but for a fallible `f`, the value is
+	/// returned whether `f` succeeds, fails or panics. The error is handed
+	/// back untouched.
+	#[track_caller]
+	pub fn with_stolen_try<R, E>(
+		&mut self,
+		f: impl FnOnce(&mut T) -> Result<R, E>,
+	) -> Result<R, E> {
+		self.with_stolen(|stolen| f(stolen.get_mut()))
+	}
 
-impl<T> AsRef<T> for Stolen<T> {
-	/// PANIC SAFETY: The stored value can only be `None` by you explicitly
-	/// returning it to the `StealCell`. As long as you haven't done that,
-	/// it's not going to be a None. And after that you can't even call this,
-	/// as return gives back ownership to the `StealCell`.
-	fn as_ref(&self) -> &T {
-		self.value.as_ref().unwrap()
+	/// Lends the stolen value to `f`, which has to hand it back alongside
+	/// its result, so that it can't forget about returning it without the
+	/// compiler noticing. The handed back value is returned to the cell.
+	///
+	/// Panics if already stolen, or if the handed back value can't be
+	/// returned here! Dropping the stolen value inside `f` panics as usual.
+	#[track_caller]
+	pub fn lend<R>(&mut self, f: impl FnOnce(Stolen<T>) -> (Stolen<T>, R)) -> R {
+		let (stolen, result) = f(self.steal());
+		self.return_stolen(stolen);
+		result
 	}
-}
 
-impl<T> AsMut<T> for Stolen<T> {
-	/// PANIC SAFETY: The stored value can only be `None` by you explicitly
-	/// returning it to the `StealCell`. As long as you haven't done that,
-	/// it's not going to be a None. And after that you can't even call this,
-	/// as return gives back ownership to the `StealCell`.
-	fn as_mut(&mut self) -> &mut T {
-		self.value.as_mut().unwrap()
+	/// Returns a reference to the value, or `None` if it's stolen.
+	pub fn get(&self) -> Option<&T> {
+		self.value.as_ref()
 	}
-}
 
-#[cfg(not(feature = "no_std"))]
-impl<T> Deref for Stolen<T> {
-	type Target = T;
+	/// Returns a mutable reference to the value, or `None` if it's stolen.
+	pub fn get_mut(&mut self) -> Option<&mut T> {
+		self.value.as_mut()
+	}
 
-	/// PANIC SAFETY: The stored value can only be `None` by giving ownership
-	/// away and returning it to the `StealCell`.
-	fn deref(&self) -> &Self::Target {
-		self.value.as_ref().unwrap()
+	/// Iterates over the value if it's present, and over nothing if it's
+	/// stolen.
+	pub fn iter(&self) -> core::option::Iter<'_, T> {
+		self.value.iter()
 	}
-}
 
-#[cfg(not(feature = "no_std"))]
-impl<T> DerefMut for Stolen<T> {
-	/// PANIC SAFETY: The stored value can only be `None` by giving ownership
-	/// away and returning it to the `StealCell`.
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		self.value.as_mut().unwrap()
+	/// Iterates over the value mutably if it's present, and over nothing if
+	/// it's stolen.
+	pub fn iter_mut(&mut self) -> core::option::IterMut<'_, T> {
+		self.value.iter_mut()
 	}
-}
 
-impl<T> Drop for Stolen<T> {
-	fn drop(&mut self) {
-		if self.value.is_some() {
-			panic!("You've lost a stolen value without returning it first!");
-		}
+	/// An `Option` view of the cell, `None` if the value is stolen. Useful to
+	/// reuse `Option` combinators like `map`, `filter` and `zip`.
+	pub fn as_option(&self) -> Option<&T> {
+		self.get()
 	}
-}
 
-#[cfg(test)]
-mod test {
-	use crate::{StealCell, Stolen};
+	/// A mutable `Option` view of the cell, `None` if the value is stolen.
+	///
+	/// Mutating through this view can only change the value itself, never
+	/// whether the cell is stolen or not.
+	pub fn as_option_mut(&mut self) -> Option<&mut T> {
+		self.get_mut()
+	}
 
-	/// Replaces the panic hook with a noop for the duration of the function.
-	/// Useful for `#[should_panic]` tests, to ensure backtraces don't pollute
-	/// stdout.
-	fn mute_panic(fun: impl FnOnce()) {
-		let hook = std::panic::take_hook();
-		std::panic::set_hook(Box::new(|_| {}));
-		fun();
-		std::panic::set_hook(hook);
+	/// Like [AsRef::as_ref] but the panic message is prefixed with `msg`.
+	pub fn expect_ref(&self, msg: &str) -> &T {
+		self.get().unwrap_or_else(|| {
+			let error = self.already_stolen();
+			misuse!(error.violation(), "{}: {}", msg, error)
+		})
 	}
 
-	struct Thing {
-		value: usize,
+	/// Like [AsMut::as_mut] but the panic message is prefixed with `msg`.
+	pub fn expect_mut(&mut self, msg: &str) -> &mut T {
+		match self.value {
+			Some(ref mut value) => value,
+			None => {
+				let error = self.already_stolen();
+				misuse!(error.violation(), "{}: {}", msg, error)
+			}
+		}
 	}
 
-	impl Default for Thing {
-		fn default() -> Self {
-			Self { value: 99 }
+	/// Calls `f` with a mutable reference to the value, handing back its
+	/// result. The value never leaves the cell, so there is nothing to
+	/// return afterwards.
+	///
+	/// Panics if stolen, reporting where it was stolen!
+	#[track_caller]
+	pub fn modify<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R {
+		self.try_modify(f)
+			.unwrap_or_else(|error| misuse!(error.violation(), "{}", error))
+	}
+
+	/// Like [StealCell::modify] but instead of panicking, returns an error
+	/// if the value is stolen, without calling `f`.
+	pub fn try_modify<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> Result<R, StealError> {
+		match &mut self.value {
+			Some(value) => Ok(f(value)),
+			None => Err(self.already_stolen()),
 		}
 	}
 
-	struct World {
-		thing: StealCell<Thing>,
+	/// Replaces the value with what `f` makes of it, by value, without
+	/// handing out a [Stolen] in between.
+	///
+	/// If `f` panics, the value is lost and the cell is left empty, as if
+	/// its value was stolen here and lost, poisoning it with the `poison`
+	/// feature. The panic carries on as is, it doesn't abort the process
+	/// even with the `abort_on_loss` feature.
+	///
+	/// Panics if stolen, reporting where it was stolen!
+	#[track_caller]
+	pub fn map_in_place(&mut self, f: impl FnOnce(T) -> T) {
+		let Ok(()) = self.try_map_in_place(|value| Ok::<T, Infallible>(f(value)));
 	}
 
-	#[test]
-	fn it_does_its_job() {
-		let mut world = World {
-			thing: StealCell::new(Thing { value: 1 }),
+	/// Like [StealCell::map_in_place] but for a fallible `f`. On error, the
+	/// value is consumed by `f` and the cell is left empty, like after
+	/// [StealCell::take], and the error is handed back.
+	///
+	/// Panics if stolen, reporting where it was stolen!
+	#[track_caller]
+	pub fn try_map_in_place<E>(&mut self, f: impl FnOnce(T) -> Result<T, E>) -> Result<(), E> {
+		let Some(value) = self.value.take() else {
+			let error = self.already_stolen();
+			misuse!(error.violation(), "{}", error)
 		};
-		assert!(!world.thing.is_stolen());
-		let mut stolen_thing = world.thing.steal();
-		assert!(world.thing.is_stolen());
-		assert_eq!(stolen_thing.as_ref().value, 1);
-		assert_eq!(stolen_thing.as_mut().value, 1);
-		world.thing.return_stolen(stolen_thing);
-		assert_eq!(world.thing.as_ref().value, 1);
-		assert_eq!(world.thing.as_mut().value, 1);
-		assert!(!world.thing.is_stolen());
+		let stolen_at = self.trace.set_stolen_at(Some(Location::caller()));
+		#[cfg(feature = "poison")]
+		let mut poison = PoisonOnUnwind(Some(self.poison_flag()));
+		let result = f(value);
+		#[cfg(feature = "poison")]
+		poison.0.take();
+
+		match result {
+			Ok(value) => {
+				self.trace.set_stolen_at(stolen_at);
+				self.value = Some(value);
+				Ok(())
+			}
+			Err(error) => {
+				self.forget_steal();
+				Err(error)
+			}
+		}
 	}
 
-	#[test]
-	fn defaults() {
-		let stealcell = StealCell::<Thing>::default();
-		assert_eq!(stealcell.as_ref().value, 99);
+	/// Returns `true` if the value is present and equal to `other`, and
+	/// `false` if stolen.
+	pub fn contains<U>(&self, other: &U) -> bool
+	where
+		T: PartialEq<U>,
+	{
+		self.get().is_some_and(|value| value == other)
 	}
 
-	#[test]
-	#[cfg(not(feature = "no_std"))]
-	fn derefs() {
-		let stealcell = StealCell::<usize>::new(12);
-		assert_eq!(*stealcell, 12);
+	/// Compares only the values of the two cells, the same as `==`. A stolen
+	/// cell is never equal to anything, not even to another stolen cell.
+	pub fn value_eq(&self, other: &StealCell<T>) -> bool
+	where
+		T: PartialEq,
+	{
+		self.get()
+			.zip(other.get())
+			.is_some_and(|(value, other)| value == other)
 	}
 
-	#[test]
-	#[cfg(not(feature = "no_std"))]
-	fn deref_muts() {
-		let mut stealcell = StealCell::<usize>::new(1);
-		*stealcell = 12;
-		assert_eq!(*stealcell, 12);
+	/// Compares the cells as if they were options, so two stolen cells are
+	/// equal, unlike with `==`.
+	pub fn option_eq(&self, other: &StealCell<T>) -> bool
+	where
+		T: PartialEq,
+	{
+		self.value == other.value
 	}
 
-	#[test]
-	#[should_panic]
-	fn panics_on_unnecessary_return() {
-		let mut stealcell = StealCell::<usize>::new(1);
-		mute_panic(|| stealcell.return_stolen(Stolen { value: Some(12) }));
+	/// Calls `f` with the value if it's present, and hands back the cell.
+	/// Does nothing when stolen.
+	pub fn inspect(&self, f: impl FnOnce(&T)) -> &Self {
+		if let Some(value) = self.get() {
+			f(value);
+		}
+		self
 	}
 
-	#[test]
-	#[should_panic]
-	fn panics_when_returning_nothing() {
-		let mut stealcell = StealCell::<usize>::new(1);
-		let mut actual_stolen = stealcell.steal();
-		actual_stolen.value = None; // Disarming for the test
+	pub fn is_stolen(&self) -> bool {
+		self.value.is_none()
+	}
 
-		mute_panic(|| stealcell.return_stolen(Stolen { value: None }));
+	/// Returns `true` if the value stolen from this cell was lost without
+	/// being returned. Reading from a poisoned cell panics with a message
+	/// saying so, and it can be recovered by refilling it, like with
+	/// [StealCell::set].
+	///
+	/// Only tracked with the `poison` feature, which costs an allocation
+	/// shared between the cell and its stolen value. Without it, this is
+	/// always `false`.
+	pub fn is_poisoned(&self) -> bool {
+		#[cfg(feature = "poison")]
+		if let Some(poison) = &self.poison {
+			return self.is_stolen() && poison.load(Ordering::Acquire);
+		}
+		false
 	}
 
-	mod stolen {
+	/// Returns the state the cell is in, meant to be matched on.
+	pub fn status(&self) -> CellState {
+		if self.is_poisoned() {
+			CellState::Poisoned
+		} else if self.is_stolen() {
+			CellState::Stolen
+		} else {
+			CellState::Present
+		}
+	}
 
-		use super::*;
-		#[test]
-		#[should_panic]
-		fn panics_when_dropped() {
-			let mut stealcell = StealCell::<Thing>::default();
-			let stolen = stealcell.steal();
-			mute_panic(|| drop(stolen));
+	/// Panics if wasn't stolen, if the value was stolen from a different
+	/// cell, or if the returned value was already consumed!
+	///
+	/// With the `debug_thread_affinity` feature, it also panics if the value
+	/// was stolen on a different thread, even when using the `try_` variant.
+	pub fn return_stolen(&mut self, stolen: Stolen<T>) {
+		if let Err(error) = self.try_return_stolen(stolen) {
+			Self::fail_return(error);
+		}
+	}
+
+	/// Like [StealCell::return_stolen] but also consumes the proof handed
+	/// out by [StealCell::steal_with_proof].
+	///
+	/// Panics if the proof belongs to a different cell, and in the same cases
+	/// as [StealCell::return_stolen]!
+	#[cfg(any(target_has_atomic = "ptr", feature = "portable_atomic"))]
+	pub fn return_stolen_with_proof(&mut self, stolen: Stolen<T>, proof: MustReturn) {
+		if self.trace.assigned_id() != proof.cell {
+			let violation = StealViolation::new(
+				ViolationKind::WrongCell,
+				proof.type_name,
+				proof.label,
+				proof.stolen_at,
+			);
+			if !handle_violation(|| violation) {
+				violation!(
+					violation,
+					"You've returned a stolen value with the proof of a different cell! {}{}",
+					ShortTypeName(proof.type_name),
+					proof.stolen_from()
+				);
+			}
+		}
+		proof.settle();
+		self.return_stolen(stolen);
+	}
+
+	/// Like [StealCell::return_stolen] but the panic message is prefixed
+	/// with `msg`.
+	pub fn expect_return_stolen(&mut self, stolen: Stolen<T>, msg: &str) {
+		if let Err(error) = self.try_return_stolen(stolen) {
+			let error = Self::disarm(error);
+			if !Self::report_failed_return(&error) {
+				violation!(error.violation(), "{}: {}", msg, error);
+			}
+		}
+	}
+
+	/// Like [StealCell::return_stolen] but accepts values stolen from other
+	/// cells too.
+	///
+	/// Panics if wasn't stolen, or if the returned value was already
+	/// consumed!
+	pub fn return_stolen_unchecked(&mut self, stolen: Stolen<T>) {
+		if let Err(error) = self.try_return_stolen_inner(stolen, false) {
+			let error = Self::disarm(error);
+			if !Self::report_failed_return(&error) {
+				violation!(error.violation(), "{}", error);
+			}
+		}
+	}
+
+	/// Like [StealCell::return_stolen] but the value is transformed by `f`
+	/// on its way back in.
+	///
+	/// Panics in the same cases as [StealCell::return_stolen]. If `f` panics,
+	/// the value is lost and the cell stays empty.
+	pub fn return_stolen_with(&mut self, stolen: Stolen<T>, f: impl FnOnce(T) -> T) {
+		match self.try_return_stolen(stolen) {
+			Ok(()) => self.value = self.value.take().map(f),
+			Err(error) => Self::fail_return(error),
+		}
+	}
+
+	/// Like [StealCell::return_stolen] but instead of panicking, returns an
+	/// error if the value can't be returned.
+	///
+	/// When the cell is occupied, the stolen value is handed back in the
+	/// error, still armed.
+	pub fn try_return_stolen(&mut self, stolen: Stolen<T>) -> Result<(), ReturnError<T>> {
+		self.try_return_stolen_inner(stolen, true).map(drop)
+	}
+
+	/// Like [StealCell::return_stolen] but hands back the placeholder put in
+	/// by [StealCell::steal_replace] instead of dropping it, or `None` if the
+	/// value was stolen without one.
+	///
+	/// Panics in the same cases as [StealCell::return_stolen].
+	pub fn return_and_take_placeholder(&mut self, stolen: Stolen<T>) -> Option<T> {
+		match self.try_return_stolen_inner(stolen, true) {
+			Ok(placeholder) => placeholder,
+			Err(error) => {
+				let error = Self::disarm(error);
+				if !Self::report_failed_return(&error) {
+					violation!(error.violation(), "{}", error);
+				}
+				None
+			}
+		}
+	}
+
+	/// Hands back the placeholder the returned value takes the place of.
+	fn try_return_stolen_inner(
+		&mut self,
+		mut stolen: Stolen<T>,
+		check_origin: bool,
+	) -> Result<Option<T>, ReturnError<T>> {
+		if stolen.value.is_none() {
+			return Err(ReturnError::AlreadyReturned);
+		}
+
+		#[cfg(feature = "debug_thread_affinity")]
+		if let Some(thread) = stolen.trail.thread {
+			let current = std::thread::current().id();
+			if thread != current {
+				stolen.value.take();
+				violation!(
+					StealViolation::new(
+						ViolationKind::WrongThread,
+						value_type_name::<T>(),
+						stolen.trail.label,
+						stolen.stolen_at
+					),
+					"trying to return a stolen value on a different thread than it was stolen on! {}, stolen on {:?}, returned on {:?}",
+					ShortTypeName(value_type_name::<T>()),
+					thread,
+					current
+				);
+			}
+		}
+
+		let origin = stolen.origin.filter(|_| check_origin);
+		if origin.is_some_and(|origin| origin.cell != self.trace.assigned_id()) {
+			return Err(ReturnError::WrongCell(stolen));
+		}
+
+		let placeholder = origin.is_some_and(|origin| {
+			origin.placeholder && origin.generation == self.trace.generation()
+		});
+		if self.value.is_some() && !placeholder {
+			return Err(ReturnError::CellOccupied(stolen));
+		}
+
+		if origin.is_some_and(|origin| origin.generation != self.trace.generation()) {
+			return Err(ReturnError::Stale(stolen));
+		}
+
+		#[cfg(feature = "deadline")]
+		self.report_overdue(stolen.trail.stolen_since);
+		#[cfg(feature = "stats")]
+		{
+			self.returns += 1;
+		}
+		#[cfg(feature = "test_util")]
+		stolen.trail.settle();
+		Ok(core::mem::replace(&mut self.value, stolen.value.take()))
+	}
+
+	/// Permanently removes the value, leaving the cell empty. Unlike
+	/// [StealCell::steal] there is no obligation to return it.
+	///
+	/// Returns `None` if the value is already stolen.
+	pub fn take(&mut self) -> Option<T> {
+		let value = self.value.take();
+		if value.is_some() {
+			self.forget_steal();
+		}
+		value
+	}
+
+	/// Like [StealCell::take] but only removes the value if `predicate`
+	/// returns `true` for it. When stolen, `predicate` is not called.
+	pub fn take_if(&mut self, predicate: impl FnOnce(&mut T) -> bool) -> Option<T> {
+		let value = self.value.take_if(predicate);
+		if value.is_some() {
+			self.forget_steal();
+		}
+		value
+	}
+
+	/// Fills an empty cell with a brand new value. When the cell is already
+	/// occupied, the value is handed back in the `Err`.
+	///
+	/// Values stolen before refilling the cell are stale, they can't be
+	/// returned even after the new value is taken out.
+	pub fn set(&mut self, value: T) -> Result<(), T> {
+		if self.value.is_some() {
+			return Err(value);
+		}
+		self.refill(value);
+		Ok(())
+	}
+
+	/// Puts a new value into the cell, returning the previous one, or `None`
+	/// if it was stolen, in which case the new value fills the hole.
+	///
+	/// Filling the hole doesn't make the outstanding [Stolen] value go away,
+	/// trying to return it afterwards will panic as the cell is occupied, or
+	/// as it's stale once the new value is gone. The same goes for values
+	/// stolen with [StealCell::steal_replace] once their placeholder is
+	/// replaced.
+	pub fn replace(&mut self, value: T) -> Option<T> {
+		match self.value.take() {
+			Some(previous) => {
+				// It might be a placeholder, the value stolen with it can't
+				// take the place of the new one.
+				self.trace.refilled();
+				self.value = Some(value);
+				Some(previous)
+			}
+			None => {
+				self.refill(value);
+				None
+			}
+		}
+	}
+
+	/// Moves the value into the empty `destination` cell in one step, without
+	/// stealing it, leaving this cell empty as if it was taken. Values stolen
+	/// from `destination` before are stale afterwards, just like after
+	/// [StealCell::set].
+	///
+	/// Fails without changing either cell if this cell is empty or if
+	/// `destination` is occupied.
+	pub fn transfer_to(&mut self, destination: &mut StealCell<T>) -> Result<(), TransferError> {
+		if self.is_stolen() {
+			return Err(TransferError::SourceStolen(self.already_stolen()));
+		}
+		if !destination.is_stolen() {
+			return Err(TransferError::DestinationOccupied {
+				type_name: value_type_name::<T>(),
+				label: destination.label(),
+			});
+		}
+		// PANIC SAFETY: Both checked above.
+		destination.set(self.take().unwrap()).ok().unwrap();
+		Ok(())
+	}
+
+	/// Exchanges the values of two occupied cells.
+	///
+	/// Empty cells can't be swapped, not even with an occupied one. Moving
+	/// a value into the hole of a stolen cell would leave its stolen value
+	/// with nowhere to go back to, use [StealCell::transfer_to] to fill
+	/// empty cells instead.
+	///
+	/// Panics if either cell is empty!
+	#[track_caller]
+	pub fn swap(&mut self, other: &mut StealCell<T>) {
+		if let Err(error) = self.try_swap(other) {
+			misuse!(error.violation(), "{}", error);
+		}
+	}
+
+	/// Like [StealCell::swap] but instead of panicking, returns an error
+	/// saying which cells are empty, and why.
+	pub fn try_swap(&mut self, other: &mut StealCell<T>) -> Result<(), SwapError> {
+		match (self.is_stolen(), other.is_stolen()) {
+			(false, false) => {
+				// Either might be a placeholder, see StealCell::replace.
+				self.trace.refilled();
+				other.trace.refilled();
+				core::mem::swap(&mut self.value, &mut other.value);
+				Ok(())
+			}
+			(true, false) => Err(SwapError::ThisStolen(self.already_stolen())),
+			(false, true) => Err(SwapError::OtherStolen(other.already_stolen())),
+			(true, true) => Err(SwapError::BothStolen(
+				self.already_stolen(),
+				other.already_stolen(),
+			)),
+		}
+	}
+
+	/// Returns a mutable reference to the value, refilling the cell first
+	/// using `f` if it's empty. `f` is not called when the value is present.
+	pub fn get_or_insert_with(&mut self, f: impl FnOnce() -> T) -> &mut T {
+		if self.value.is_none() {
+			self.refill(f());
+		}
+		// PANIC SAFETY: Filled right above.
+		self.value.as_mut().unwrap()
+	}
+
+	/// Consumes the cell, handing over its value if it has one, or `None` if
+	/// it was stolen. No strings attached, there is nothing to return.
+	pub fn into_inner(self) -> Option<T> {
+		self.value
+	}
+
+	/// Consumes the cell, converting it into a cell of another type using
+	/// `f`. A stolen cell stays stolen without calling `f`.
+	pub fn map_cell<U>(self, f: impl FnOnce(T) -> U) -> StealCell<U> {
+		let Ok(cell) = self.try_map_cell(|value| Ok::<U, Infallible>(f(value)));
+		cell
+	}
+
+	/// Consumes the cell, converting it into a cell of another type using
+	/// the fallible `f`. A stolen cell stays stolen without calling `f`.
+	///
+	/// Note that on error, the original value is lost to `f`.
+	pub fn try_map_cell<U, E>(self, f: impl FnOnce(T) -> Result<U, E>) -> Result<StealCell<U>, E> {
+		Ok(StealCell::from(self.value.map(f).transpose()?))
+	}
+
+	/// In case we'd need to panic, the value is taken first so that the
+	/// stolen struct dropping doesn't cause another extra panic.
+	fn disarm(mut error: ReturnError<T>) -> ReturnError<T> {
+		if let ReturnError::CellOccupied(stolen)
+		| ReturnError::WrongCell(stolen)
+		| ReturnError::Stale(stolen) = &mut error
+		{
+			stolen.value.take();
+		}
+		error
+	}
+
+	/// Panics about a value that couldn't be stolen, also used by
+	/// [SyncStealCell](crate::sync::SyncStealCell) once it let go of its
+	/// lock.
+	#[track_caller]
+	pub(crate) fn fail_steal(error: StealError) -> ! {
+		Self::report_failed_steal(&error);
+		violation!(error.violation(), "{}", error)
+	}
+
+	/// Panics about a value that couldn't be returned, unless the violation
+	/// handler took care of it.
+	pub(crate) fn fail_return(error: ReturnError<T>) {
+		let error = Self::disarm(error);
+		if !Self::report_failed_return(&error) {
+			violation!(error.violation(), "{}", error);
+		}
+	}
+
+	/// Lets the violation handler know before panicking, as there is no
+	/// value to hand out either way.
+	fn report_failed_steal(error: &StealError) {
+		handle_violation(|| error.violation());
+	}
+
+	/// Returns `true` if the violation handler took care of the disarmed
+	/// value, otherwise the caller panics.
+	fn report_failed_return(error: &ReturnError<T>) -> bool {
+		handle_violation(|| error.violation())
+	}
+
+	/// Puts a new value into the empty cell, making earlier stolen values
+	/// stale.
+	fn refill(&mut self, value: T) {
+		self.trace.refilled();
+		self.value = Some(value);
+	}
+
+	/// Wraps a value just taken out of the cell, remembering where it was
+	/// stolen from.
+	#[track_caller]
+	fn stolen(&mut self, value: T) -> Stolen<T> {
+		self.stolen_at(value, Location::caller())
+	}
+
+	/// Like [StealCell::stolen], but remembering where it was stolen from.
+	fn stolen_at(&mut self, value: T, stolen_at: &'static Location<'static>) -> Stolen<T> {
+		self.trace.set_stolen_at(Some(stolen_at));
+		let origin = Origin {
+			cell: self.trace.id(),
+			generation: self.trace.generation(),
+			placeholder: false,
+		};
+		let mut stolen = Stolen::new(value, Some(origin), Some(stolen_at));
+		stolen.trail.label = self.trace.label();
+		#[cfg(feature = "stats")]
+		{
+			self.steals += 1;
+		}
+		#[cfg(feature = "debug_thread_affinity")]
+		{
+			stolen.trail.thread = Some(std::thread::current().id());
+		}
+		#[cfg(feature = "steal_timing")]
+		{
+			stolen.trail.stolen_since = Some(Instant::now());
+		}
+		#[cfg(feature = "deadline")]
+		{
+			self.stolen_since = stolen.trail.stolen_since;
+		}
+		#[cfg(feature = "poison")]
+		{
+			stolen.poison = Some(self.poison_flag());
+		}
+		#[cfg(feature = "salvage")]
+		{
+			stolen.salvage = self.salvage.clone();
+		}
+		#[cfg(feature = "test_util")]
+		if let Some(tracking) = &self.tracking {
+			tracking.stolen(
+				self.trace.generation(),
+				crate::OutstandingSteal {
+					type_name: value_type_name::<T>(),
+					label: self.trace.label(),
+					stolen_at: Some(stolen_at),
+				},
+			);
+			stolen.trail.tracked = Some((tracking.0, self.trace.generation()));
+		}
+		stolen
+	}
+
+	/// Reuses the flag if the previous stolen value is long gone, which is
+	/// the usual case.
+	#[cfg(feature = "poison")]
+	fn poison_flag(&mut self) -> Arc<AtomicBool> {
+		match &mut self.poison {
+			Some(poison) if Arc::strong_count(poison) == 1 => {
+				poison.store(false, Ordering::Release);
+				poison.clone()
+			}
+			_ => self.poison.insert(Arc::new(AtomicBool::new(false))).clone(),
+		}
+	}
+
+	/// Forgets about any earlier steal once the value is taken out by hand.
+	fn forget_steal(&mut self) {
+		self.trace.set_stolen_at(None);
+		#[cfg(feature = "deadline")]
+		{
+			self.stolen_since = None;
+		}
+		#[cfg(feature = "poison")]
+		{
+			self.poison = None;
+		}
+	}
+
+	pub(crate) fn already_stolen(&self) -> StealError {
+		if self.is_poisoned() {
+			StealError::Poisoned {
+				type_name: value_type_name::<T>(),
+				stolen_at: self.trace.stolen_at(),
+				label: self.trace.label(),
+			}
+		} else {
+			StealError::AlreadyStolen {
+				type_name: value_type_name::<T>(),
+				stolen_at: self.trace.stolen_at(),
+				label: self.trace.label(),
+			}
+		}
+	}
+}
+
+impl<T> StealCell<T>
+where
+	T: Default,
+{
+	/// Like [StealCell::steal], but if the value was already stolen, a
+	/// default value is stolen instead. Either way the cell ends up in the
+	/// stolen state and the value has to be returned.
+	#[track_caller]
+	pub fn steal_or_default(&mut self) -> Stolen<T> {
+		let value = self.value.take().unwrap_or_default();
+		self.stolen(value)
+	}
+}
+
+impl<T> StealCell<T>
+where
+	T: Clone,
+{
+	/// Clones the value out, leaving it in the cell, or `None` if it's
+	/// stolen. Unlike stealing, the clone is yours to keep, there is nothing
+	/// to return.
+	pub fn steal_clone(&self) -> Option<T> {
+		self.value.clone()
+	}
+
+	/// Steals the value, leaving a clone of it in the cell for anything
+	/// reading it meanwhile, see [StealCell::steal_replace]. Returning the
+	/// value overwrites the snapshot with it.
+	///
+	/// Readers see the value as it was when stolen, not the changes made to
+	/// the stolen value until it's returned, so they may act on stale data.
+	///
+	/// Panics if already stolen!
+	#[track_caller]
+	pub fn steal_with_snapshot(&mut self) -> Stolen<T> {
+		let Some(snapshot) = self.value.clone() else {
+			return self.steal();
+		};
+		self.steal_replace(snapshot)
+	}
+}
+
+impl<T> StealCell<T>
+where
+	T: Copy,
+{
+	/// Copies the value out, or `None` if it's stolen, like
+	/// [Option::copied].
+	pub fn copied(&self) -> Option<T> {
+		self.value
+	}
+
+	/// Copies the value out.
+	///
+	/// Panics if stolen, reporting where it was stolen!
+	#[track_caller]
+	pub fn get_copy(&self) -> T {
+		self.copied().unwrap_or_else(|| {
+			let error = self.already_stolen();
+			misuse!(error.violation(), "{}", error)
+		})
+	}
+}
+
+impl<T> StealCell<T>
+where
+	T: StealPlaceholder,
+{
+	/// Like [StealCell::steal_replace], with the placeholder of the type.
+	///
+	/// Panics if already stolen!
+	#[track_caller]
+	pub fn steal_replace_placeholder(&mut self) -> Stolen<T> {
+		self.steal_replace(T::placeholder())
+	}
+}
+
+impl<T> StealCell<T>
+where
+	T: Deref,
+{
+	/// Returns a reference to the target of the value, or `None` if it's
+	/// stolen. Just like `Option::as_deref`.
+	pub fn as_deref(&self) -> Option<&T::Target> {
+		self.value.as_deref()
+	}
+}
+
+impl<T> StealCell<T>
+where
+	T: DerefMut,
+{
+	/// Returns a mutable reference to the target of the value, or `None` if
+	/// it's stolen. Just like `Option::as_deref_mut`.
+	pub fn as_deref_mut(&mut self) -> Option<&mut T::Target> {
+		self.value.as_deref_mut()
+	}
+}
+
+impl<T> From<T> for StealCell<T> {
+	fn from(value: T) -> Self {
+		Self::new(value)
+	}
+}
+
+impl<T> From<Option<T>> for StealCell<T> {
+	/// `None` becomes a stolen cell.
+	fn from(value: Option<T>) -> Self {
+		Self {
+			value,
+			trace: Trace::default(),
+			#[cfg(feature = "poison")]
+			poison: None,
+			#[cfg(feature = "deadline")]
+			deadline: None,
+			#[cfg(feature = "deadline")]
+			stolen_since: None,
+			#[cfg(feature = "stats")]
+			steals: 0,
+			#[cfg(feature = "stats")]
+			returns: 0,
+			#[cfg(feature = "test_util")]
+			tracking: None,
+			#[cfg(feature = "salvage")]
+			salvage: None,
+		}
+	}
+}
+
+impl<T> From<StealCell<T>> for Option<T> {
+	/// A stolen cell becomes `None`.
+	fn from(stealcell: StealCell<T>) -> Self {
+		stealcell.into_inner()
+	}
+}
+
+impl<T> PartialEq<T> for StealCell<T>
+where
+	T: PartialEq,
+{
+	/// Always `false` when stolen.
+	fn eq(&self, other: &T) -> bool {
+		self.contains(other)
+	}
+}
+
+impl<T> PartialEq<Stolen<T>> for StealCell<T>
+where
+	T: PartialEq,
+{
+	/// Always `false` when stolen.
+	fn eq(&self, other: &Stolen<T>) -> bool {
+		other
+			.value
+			.as_ref()
+			.is_some_and(|other| self.contains(other))
+	}
+}
+
+impl<T> AsRef<T> for StealCell<T> {
+	/// Panics if stolen!
+	fn as_ref(&self) -> &T {
+		self.get().unwrap_or_else(|| {
+			let error = self.already_stolen();
+			misuse!(error.violation(), "{}", error)
+		})
+	}
+}
+
+impl<T> AsMut<T> for StealCell<T> {
+	/// Panics if stolen!
+	fn as_mut(&mut self) -> &mut T {
+		match self.value {
+			Some(ref mut value) => value,
+			None => {
+				let error = self.already_stolen();
+				misuse!(error.violation(), "{}", error)
+			}
+		}
+	}
+}
+
+impl<T> Borrow<T> for StealCell<T> {
+	/// Panics if stolen!
+	///
+	/// Note that `Borrow` expects `Eq` and `Hash` to behave the same on the
+	/// borrowed value as on the cell, which can't hold for a stolen cell.
+	fn borrow(&self) -> &T {
+		self.as_ref()
+	}
+}
+
+impl<T> BorrowMut<T> for StealCell<T> {
+	/// Panics if stolen!
+	fn borrow_mut(&mut self) -> &mut T {
+		self.as_mut()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> Deref for StealCell<T> {
+	type Target = T;
+
+	/// PANIC SAFETY: The stored value can only be `None` by giving ownership
+	/// away and returning it to the `StealCell`.
+	fn deref(&self) -> &Self::Target {
+		self.as_ref()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> DerefMut for StealCell<T> {
+	/// PANIC SAFETY: The stored value can only be `None` by giving ownership
+	/// away and returning it to the `StealCell`.
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.as_mut()
+	}
+}
+
+/// Yields the value if it's present, and nothing if it's stolen, like
+/// [Option] does. Consuming the cell leaves nothing to return.
+impl<T> IntoIterator for StealCell<T> {
+	type Item = T;
+	type IntoIter = core::option::IntoIter<T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.value.into_iter()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a StealCell<T> {
+	type Item = &'a T;
+	type IntoIter = core::option::Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a mut StealCell<T> {
+	type Item = &'a mut T;
+	type IntoIter = core::option::IterMut<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter_mut()
+	}
+}
+
+/// A value stolen from a [StealCell]. If you accidentally drop it before
+/// returning it where it belongs, it will panic!
+#[must_use = "a stolen value must be returned with return_stolen or it will panic on drop"]
+pub struct Stolen<T> {
+	/// Starts out as Some, becomes None once returned.
+	/// If not returned, panics!
+	value: Option<T>,
+	/// The cell it was stolen from. `None` if it can't be traced back to a
+	/// single cell, in which case it can be returned to any cell.
+	origin: Option<Origin>,
+	/// Where the value was stolen, reported when it's lost.
+	stolen_at: Option<&'static Location<'static>>,
+	/// Carried along when the value is split or mapped.
+	trail: Trail,
+	/// Shared with the cell it was stolen from, raised when lost.
+	#[cfg(feature = "poison")]
+	poison: Option<Arc<AtomicBool>>,
+	/// Called with the value instead of dropping it when it's lost.
+	#[cfg(feature = "salvage")]
+	salvage: Option<SalvageHook<T>>,
+}
+
+/// What a cell remembers about itself and its steals, to tell its stolen
+/// values apart and to report them. Only kept in debug builds or with the
+/// `diagnostics` feature, otherwise it's empty.
+#[derive(Default)]
+struct Trace {
+	/// Assigned on the first steal.
+	#[cfg(all(
+		any(debug_assertions, feature = "diagnostics"),
+		any(target_has_atomic = "ptr", feature = "portable_atomic")
+	))]
+	id: Option<NonZeroUsize>,
+	/// Incremented every time the cell is refilled with a new value.
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	generation: usize,
+	/// Where the value was last stolen from the cell, reported when trying
+	/// to steal it again.
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	stolen_at: Option<&'static Location<'static>>,
+	/// Included in panic and error messages, see [StealCell::new_named].
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	label: Option<&'static str>,
+}
+
+impl Trace {
+	/// The id of the cell, assigned on the first call, or `None` when cells
+	/// aren't told apart.
+	fn id(&mut self) -> Option<NonZeroUsize> {
+		#[cfg(all(
+			any(debug_assertions, feature = "diagnostics"),
+			any(target_has_atomic = "ptr", feature = "portable_atomic")
+		))]
+		return Some(*self.id.get_or_insert_with(next_cell_id));
+		#[cfg(not(all(
+			any(debug_assertions, feature = "diagnostics"),
+			any(target_has_atomic = "ptr", feature = "portable_atomic")
+		)))]
+		None
+	}
+
+	/// Like [Trace::id] but without assigning one.
+	fn assigned_id(&self) -> Option<NonZeroUsize> {
+		#[cfg(all(
+			any(debug_assertions, feature = "diagnostics"),
+			any(target_has_atomic = "ptr", feature = "portable_atomic")
+		))]
+		return self.id;
+		#[cfg(not(all(
+			any(debug_assertions, feature = "diagnostics"),
+			any(target_has_atomic = "ptr", feature = "portable_atomic")
+		)))]
+		None
+	}
+
+	fn generation(&self) -> usize {
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		return self.generation;
+		#[cfg(not(any(debug_assertions, feature = "diagnostics")))]
+		0
+	}
+
+	/// Makes values stolen before stale.
+	fn refilled(&mut self) {
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		{
+			self.generation = self.generation.wrapping_add(1);
+		}
+	}
+
+	fn stolen_at(&self) -> Option<&'static Location<'static>> {
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		return self.stolen_at;
+		#[cfg(not(any(debug_assertions, feature = "diagnostics")))]
+		None
+	}
+
+	/// Hands back where it was stolen before.
+	#[cfg_attr(
+		not(any(debug_assertions, feature = "diagnostics")),
+		expect(unused_variables)
+	)]
+	fn set_stolen_at(
+		&mut self,
+		stolen_at: Option<&'static Location<'static>>,
+	) -> Option<&'static Location<'static>> {
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		return core::mem::replace(&mut self.stolen_at, stolen_at);
+		#[cfg(not(any(debug_assertions, feature = "diagnostics")))]
+		None
+	}
+
+	fn label(&self) -> Option<&'static str> {
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		return self.label;
+		#[cfg(not(any(debug_assertions, feature = "diagnostics")))]
+		None
+	}
+
+	#[cfg_attr(
+		not(any(debug_assertions, feature = "diagnostics")),
+		expect(unused_variables)
+	)]
+	fn set_label(&mut self, label: &'static str) {
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		{
+			self.label = Some(label);
+		}
+	}
+}
+
+/// What a stolen value remembers about the steal besides where it happened,
+/// kept when it's split or mapped.
+#[derive(Debug, Clone, Copy, Default)]
+struct Trail {
+	/// The label of the cell it was stolen from, reported when it's lost.
+	label: Option<&'static str>,
+	/// The thread it was stolen on, it can only be returned there.
+	#[cfg(feature = "debug_thread_affinity")]
+	thread: Option<std::thread::ThreadId>,
+	/// When it was stolen, reported when it's lost, and to check the
+	/// deadline of the cell.
+	#[cfg(feature = "steal_timing")]
+	stolen_since: Option<Instant>,
+	/// The cell and generation it's registered under, if stolen from a
+	/// tracked cell.
+	#[cfg(feature = "test_util")]
+	tracked: Option<(NonZeroUsize, usize)>,
+}
+
+impl Trail {
+	/// Unregisters the stolen value once it's no longer owed.
+	#[cfg(feature = "test_util")]
+	fn settle(&self) {
+		if let Some((cell, generation)) = self.tracked {
+			crate::registry::returned(cell, generation);
+		}
+	}
+}
+
+/// Identifies the cell a value was stolen from, and which value it was, as
+/// far as the cell keeps track of it, see [Trace].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Origin {
+	cell: Option<NonZeroUsize>,
+	generation: usize,
+	/// Stolen with [StealCell::steal_replace], whatever is in the cell of
+	/// the same generation is the placeholder.
+	placeholder: bool,
+}
+
+impl<T> Stolen<T> {
+	fn new(
+		value: T,
+		origin: Option<Origin>,
+		stolen_at: Option<&'static Location<'static>>,
+	) -> Self {
+		Self {
+			value: Some(value),
+			origin,
+			stolen_at,
+			trail: Trail::default(),
+			#[cfg(feature = "poison")]
+			poison: None,
+			#[cfg(feature = "salvage")]
+			salvage: None,
+		}
+	}
+
+	/// A stolen value that can't be traced back to a [StealCell], for cells
+	/// that keep track of their stolen values themselves, like
+	/// [AtomicStealCell](crate::sync::AtomicStealCell).
+	#[cfg(all(
+		any(feature = "alloc", not(feature = "no_std")),
+		any(target_has_atomic = "ptr", feature = "portable_atomic")
+	))]
+	pub(crate) fn detached(
+		value: T,
+		label: Option<&'static str>,
+		stolen_at: Option<&'static Location<'static>>,
+	) -> Self {
+		let mut stolen = Self::new(value, None, stolen_at);
+		stolen.trail.label = label;
+		stolen
+	}
+
+	/// Lets the value be returned on any thread, for values meant to be sent
+	/// elsewhere.
+	#[cfg(all(feature = "debug_thread_affinity", not(feature = "no_std")))]
+	pub(crate) fn release_thread(&mut self) {
+		self.trail.thread = None;
+	}
+
+	/// Takes the value out to make another stolen value from it, which
+	/// takes over the obligation, see [Stolen::owed].
+	fn take_value(&mut self) -> T {
+		// PANIC SAFETY: Can only be `None` after being returned, which
+		// consumes the stolen value.
+		self.value.take().unwrap()
+	}
+
+	/// Makes a stolen value of `value` owed just like this one, poisoning
+	/// the same cell and registered under the same steal when lost.
+	fn owed<U>(&self, value: U, origin: Option<Origin>) -> Stolen<U> {
+		let mut stolen = Stolen::new(value, origin, self.stolen_at);
+		stolen.trail = self.trail;
+		#[cfg(feature = "poison")]
+		{
+			stolen.poison = self.poison.clone();
+		}
+		stolen
+	}
+
+	/// Lets go of the registered steal of a value merged into another one
+	/// stolen from somewhere else, see [Stolen::zip].
+	#[cfg_attr(not(feature = "test_util"), expect(unused_variables))]
+	fn merged_into<U>(&self, stolen: &Stolen<U>) {
+		#[cfg(feature = "test_util")]
+		if self.trail.tracked != stolen.trail.tracked {
+			self.trail.settle();
+		}
+	}
+
+	/// Returns the label of the cell it was stolen from, if it had one, see
+	/// [StealCell::new_named].
+	pub fn label(&self) -> Option<&'static str> {
+		self.trail.label
+	}
+
+	pub(crate) fn stolen_at(&self) -> Option<&'static Location<'static>> {
+		self.stolen_at
+	}
+
+	/// Returns a reference to the stolen value. Unlike `Deref`, this is also
+	/// available with `no_std`.
+	///
+	/// PANIC SAFETY: The stored value can only be `None` by you explicitly
+	/// returning it to the `StealCell`, which consumes the stolen value.
+	pub fn get(&self) -> &T {
+		self.value.as_ref().unwrap()
+	}
+
+	/// Returns a mutable reference to the stolen value. Unlike `DerefMut`,
+	/// this is also available with `no_std`.
+	///
+	/// PANIC SAFETY: The stored value can only be `None` by you explicitly
+	/// returning it to the `StealCell`, which consumes the stolen value.
+	pub fn get_mut(&mut self) -> &mut T {
+		self.value.as_mut().unwrap()
+	}
+
+	/// Keeps the stolen value for good, handing over plain ownership.
+	///
+	/// This is an explicit opt-out of the promise to return the value, the
+	/// cell it was stolen from stays empty.
+	pub fn into_inner(mut self) -> T {
+		#[cfg(feature = "test_util")]
+		self.trail.settle();
+		// PANIC SAFETY: Can only be `None` after being returned, which
+		// consumes the stolen value.
+		self.value.take().unwrap()
+	}
+
+	/// Calls `f` with the stolen value, and hands it back.
+	pub fn inspect(self, f: impl FnOnce(&T)) -> Self {
+		f(self.get());
+		self
+	}
+
+	/// Swaps the stolen value for a new one which will be returned in its
+	/// place, handing back the previous one.
+	pub fn replace(&mut self, value: T) -> T {
+		core::mem::replace(self.as_mut(), value)
+	}
+
+	/// Like [Stolen::return_to] but instead of panicking when it can't be
+	/// returned to `cell`, the stolen value is handed back, still armed.
+	pub fn try_return_to(self, cell: &mut StealCell<T>) -> Result<(), Stolen<T>> {
+		match cell.try_return_stolen(self) {
+			Ok(()) => Ok(()),
+			Err(
+				ReturnError::CellOccupied(stolen)
+				| ReturnError::WrongCell(stolen)
+				| ReturnError::Stale(stolen),
+			) => Err(stolen),
+			Err(error) => violation!(error.violation(), "{}", error),
+		}
+	}
+
+	/// Exchanges the stolen value with the value of another cell. The stolen
+	/// value is still armed afterwards, and has to be returned somewhere.
+	///
+	/// Panics if `cell` is stolen too!
+	pub fn swap_with(&mut self, cell: &mut StealCell<T>) {
+		core::mem::swap(self.get_mut(), cell.as_mut());
+	}
+
+	/// Intentionally drops the stolen value without returning it, and
+	/// without panicking. The cell it was stolen from stays empty, and can be
+	/// refilled using [StealCell::set].
+	pub fn discard(self) {
+		drop(self.into_inner());
+	}
+
+	/// Moves the stolen value into a brand new, occupied cell, letting go of
+	/// the obligation towards the cell it was stolen from.
+	pub fn into_cell(self) -> StealCell<T> {
+		StealCell::new(self.into_inner())
+	}
+
+	/// Transforms the stolen value, possibly into another type, to be
+	/// returned to a cell of that type. The result is just as armed as the
+	/// original was.
+	///
+	/// The result is no longer tied to the cell it was stolen from, so it
+	/// can be returned to any cell.
+	///
+	/// If `f` panics, the value is lost, but it won't cause a second panic.
+	pub fn map<U>(mut self, f: impl FnOnce(T) -> U) -> Stolen<U> {
+		let value = self.take_value();
+		self.owed(f(value), None)
+	}
+
+	/// Returns the stolen value to `cell`, the same as
+	/// [StealCell::return_stolen] just the other way around.
+	///
+	/// Panics if `cell` is not empty, or if it's not where the value was
+	/// stolen from!
+	pub fn return_to(self, cell: &mut StealCell<T>) {
+		cell.return_stolen(self);
+	}
+}
+
+impl<A, B> Stolen<(A, B)> {
+	/// Splits a stolen pair into two stolen values, both of which are armed
+	/// and have to be zipped back together using [Stolen::zip] to be
+	/// returned.
+	pub fn split(mut self) -> (Stolen<A>, Stolen<B>) {
+		let (a, b) = self.take_value();
+		(self.owed(a, self.origin), self.owed(b, self.origin))
+	}
+
+	/// Zips two stolen values back into a pair, the inverse of
+	/// [Stolen::split].
+	///
+	/// Zipping values that were stolen from different cells loses track of
+	/// where they were stolen from.
+	pub fn zip(mut a: Stolen<A>, mut b: Stolen<B>) -> Self {
+		let origin = a.origin.filter(|origin| b.origin == Some(*origin));
+		let pair = (a.take_value(), b.take_value());
+		let zipped = a.owed(pair, origin);
+		b.merged_into(&zipped);
+		zipped
+	}
+}
+
+impl<A, B, C> Stolen<(A, B, C)> {
+	/// Splits a stolen triple into three stolen values, all of which are
+	/// armed and have to be zipped back together using [Stolen::zip3] to be
+	/// returned.
+	pub fn split(mut self) -> (Stolen<A>, Stolen<B>, Stolen<C>) {
+		let (a, b, c) = self.take_value();
+		(
+			self.owed(a, self.origin),
+			self.owed(b, self.origin),
+			self.owed(c, self.origin),
+		)
+	}
+
+	/// Zips three stolen values back into a triple, the inverse of
+	/// splitting it.
+	///
+	/// Zipping values that were stolen from different cells loses track of
+	/// where they were stolen from.
+	pub fn zip3(mut a: Stolen<A>, mut b: Stolen<B>, mut c: Stolen<C>) -> Self {
+		let origin = a
+			.origin
+			.filter(|origin| b.origin == Some(*origin) && c.origin == Some(*origin));
+		let triple = (a.take_value(), b.take_value(), c.take_value());
+		let zipped = a.owed(triple, origin);
+		b.merged_into(&zipped);
+		c.merged_into(&zipped);
+		zipped
+	}
+}
+
+impl<T> Stolen<T>
+where
+	T: Copy,
+{
+	/// Copies the stolen value out, it still has to be returned.
+	pub fn copied(&self) -> T {
+		*self.get()
+	}
+}
+
+impl<T> Stolen<T>
+where
+	T: Deref,
+{
+	/// Returns a reference to the target of the stolen value.
+	pub fn as_deref(&self) -> &T::Target {
+		self.get()
+	}
+}
+
+impl<T> Stolen<T>
+where
+	T: DerefMut,
+{
+	/// Returns a mutable reference to the target of the stolen value.
+	pub fn as_deref_mut(&mut self) -> &mut T::Target {
+		self.get_mut()
+	}
+}
+
+impl<T> AsRef<T> for Stolen<T> {
+	/// PANIC SAFETY: The stored value can only be `None` by you explicitly
+	/// returning it to the `StealCell`. As long as you haven't done that,
+	/// it's not going to be a None. And after that you can't even call this,
+	/// as return gives back ownership to the `StealCell`.
+	fn as_ref(&self) -> &T {
+		self.get()
+	}
+}
+
+impl<T> AsMut<T> for Stolen<T> {
+	/// PANIC SAFETY: The stored value can only be `None` by you explicitly
+	/// returning it to the `StealCell`. As long as you haven't done that,
+	/// it's not going to be a None. And after that you can't even call this,
+	/// as return gives back ownership to the `StealCell`.
+	fn as_mut(&mut self) -> &mut T {
+		self.get_mut()
+	}
+}
+
+impl<T> Borrow<T> for Stolen<T> {
+	fn borrow(&self) -> &T {
+		self.as_ref()
+	}
+}
+
+impl<T> BorrowMut<T> for Stolen<T> {
+	fn borrow_mut(&mut self) -> &mut T {
+		self.as_mut()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> Deref for Stolen<T> {
+	type Target = T;
+
+	/// PANIC SAFETY: The stored value can only be `None` by giving ownership
+	/// away and returning it to the `StealCell`.
+	fn deref(&self) -> &Self::Target {
+		self.get()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> DerefMut for Stolen<T> {
+	/// PANIC SAFETY: The stored value can only be `None` by giving ownership
+	/// away and returning it to the `StealCell`.
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.get_mut()
+	}
+}
+
+impl<T> PartialEq for Stolen<T>
+where
+	T: PartialEq,
+{
+	fn eq(&self, other: &Self) -> bool {
+		self.value == other.value
+	}
+}
+
+impl<T> Eq for Stolen<T> where T: Eq {}
+
+impl<T> UnwindSafe for Stolen<T> where T: UnwindSafe {}
+impl<T> RefUnwindSafe for Stolen<T> where T: RefUnwindSafe {}
+
+impl<T> PartialEq<T> for Stolen<T>
+where
+	T: PartialEq,
+{
+	fn eq(&self, other: &T) -> bool {
+		self.value.as_ref().is_some_and(|value| value == other)
+	}
+}
+
+impl<T> PartialEq<StealCell<T>> for Stolen<T>
+where
+	T: PartialEq,
+{
+	/// Always `false` when the cell is stolen.
+	fn eq(&self, other: &StealCell<T>) -> bool {
+		other == self
+	}
+}
+
+impl<T> Debug for Stolen<T>
+where
+	T: Debug,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match &self.value {
+			Some(value) => f.debug_tuple("Stolen").field(value).finish(),
+			None => f.write_str("Stolen(<returned>)"),
+		}
+	}
+}
+
+/// Prints the stolen value.
+impl<T> Display for Stolen<T>
+where
+	T: Display,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		self.get().fmt(f)
+	}
+}
+
+/// Whether losing a stolen value is a violation, only disarmed by the
+/// `release_disarm` feature in release builds.
+pub(crate) const DROP_BOMB: bool = cfg!(any(debug_assertions, not(feature = "release_disarm")));
+
+impl<T> Drop for Stolen<T> {
+	/// When the thread is already panicking, panicking again would abort the
+	/// process and swallow the original panic, so the lost value is only
+	/// reported on stderr. Not available with `no_std`.
+	///
+	/// With a violation handler installed, it's called instead of either.
+	/// With the `abort_on_loss` feature, the process aborts even while
+	/// panicking.
+	///
+	/// With the `release_disarm` feature, lost values are only reported to
+	/// the violation handler in release builds, and dropped silently
+	/// otherwise, whether `abort_on_loss` is enabled or not.
+	///
+	/// With a salvage hook set using [StealCell::on_loss], the value is
+	/// handed to it before any of the above.
+	fn drop(&mut self) {
+		if self.value.is_some() {
+			let type_name = value_type_name::<T>();
+			#[cfg(feature = "steal_timing")]
+			let ago = self.trail.stolen_since.map(|since| since.elapsed());
+			#[cfg(not(feature = "steal_timing"))]
+			let ago = None;
+			let stolen_from = StolenFrom {
+				label: self.trail.label,
+				stolen_at: self.stolen_at,
+				ago,
+			};
+
+			#[cfg(feature = "poison")]
+			if let Some(poison) = &self.poison {
+				poison.store(true, Ordering::Release);
+			}
+
+			#[cfg(feature = "salvage")]
+			if let (Some(salvage), Some(value)) = (&self.salvage, self.value.take()) {
+				let mut salvage = salvage
+					.lock()
+					.unwrap_or_else(std::sync::PoisonError::into_inner);
+				salvage(value);
+			}
+
+			let violation = StealViolation::new(
+				ViolationKind::Lost,
+				type_name,
+				self.trail.label,
+				self.stolen_at,
+			);
+			if handle_violation(|| violation) || !DROP_BOMB {
+				return;
+			}
+
+			#[cfg(not(any(feature = "no_std", feature = "abort_on_loss")))]
+			if std::thread::panicking() {
+				#[cfg(feature = "slim_panics")]
+				let _ = std::io::Write::write_all(
+					&mut std::io::stderr(),
+					b"stealcell: stolen value lost while panicking\n",
+				);
+				#[cfg(not(feature = "slim_panics"))]
+				std::eprintln!(
+					"Lost a stolen value while panicking! {}{stolen_from}",
+					ShortTypeName(type_name)
+				);
+				return;
+			}
+
+			violation!(
+				violation,
+				"You've lost a stolen value without returning it first! {}{}",
+				ShortTypeName(type_name),
+				stolen_from
+			);
+		}
+	}
+}
+
+/// Formats the cell and the location a value was stolen from, and how long
+/// ago, if known.
+pub(crate) struct StolenFrom {
+	pub(crate) label: Option<&'static str>,
+	pub(crate) stolen_at: Option<&'static Location<'static>>,
+	pub(crate) ago: Option<Duration>,
+}
+
+impl fmt::Display for StolenFrom {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		if self.label.is_none() && self.stolen_at.is_none() && self.ago.is_none() {
+			return Ok(());
+		}
+		f.write_str(" stolen")?;
+		if let Some(label) = self.label {
+			write!(f, " from '{label}'")?;
+		}
+		if let Some(stolen_at) = self.stolen_at {
+			write!(f, " at {stolen_at}")?;
+		}
+		if let Some(ago) = self.ago {
+			let millis = ago.as_millis();
+			write!(
+				f,
+				" {:02}:{:02}:{:02}.{:03} ago",
+				millis / 3_600_000,
+				millis / 60_000 % 60,
+				millis / 1000 % 60,
+				millis % 1000
+			)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use core::borrow::{Borrow, BorrowMut};
+
+	use crate::{
+		CellState, ReturnError, STOLEN_MARKER, StealCell, StealError, Stolen, SwapError,
+		TransferError,
+	};
+
+	/// Replaces the panic hook with a noop for the duration of the function.
+	/// Useful for `#[should_panic]` tests, to ensure backtraces don't pollute
+	/// stdout.
+	fn mute_panic(fun: impl FnOnce()) {
+		let hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(|_| {}));
+		fun();
+		std::panic::set_hook(hook);
+	}
+
+	/// Drops the stolen value without returning it, swallowing the panic
+	/// when the drop bomb is armed.
+	#[cfg(all(
+		any(feature = "poison", feature = "salvage"),
+		not(feature = "abort_on_loss")
+	))]
+	fn lose<T>(stolen: Stolen<T>) {
+		let hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(|_| {}));
+		let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(stolen)));
+		std::panic::set_hook(hook);
+	}
+
+	/// Runs the function expecting it to panic, and returns the panic
+	/// message.
+	fn panic_message(fun: impl FnOnce()) -> String {
+		let hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(|_| {}));
+		let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(fun))
+			.expect_err("expected a panic");
+		std::panic::set_hook(hook);
+		payload
+			.downcast_ref::<String>()
+			.cloned()
+			.or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+			.expect("expected a string panic payload")
+	}
+
+	struct Thing {
+		value: usize,
+	}
+
+	impl Default for Thing {
+		fn default() -> Self {
+			Self { value: 99 }
+		}
+	}
+
+	struct World {
+		thing: StealCell<Thing>,
+	}
+
+	#[test]
+	fn it_does_its_job() {
+		let mut world = World {
+			thing: StealCell::new(Thing { value: 1 }),
+		};
+		assert!(!world.thing.is_stolen());
+		let mut stolen_thing = world.thing.steal();
+		assert!(world.thing.is_stolen());
+		assert_eq!(stolen_thing.as_ref().value, 1);
+		assert_eq!(stolen_thing.as_mut().value, 1);
+		world.thing.return_stolen(stolen_thing);
+		assert_eq!(world.thing.as_ref().value, 1);
+		assert_eq!(world.thing.as_mut().value, 1);
+		assert!(!world.thing.is_stolen());
+	}
+
+	#[test]
+	fn debug_formats_the_value_or_stolen() {
+		let mut stealcell = StealCell::<Option<usize>>::new(Some(12));
+		assert_eq!(format!("{stealcell:?}"), "StealCell(Some(12))");
+		assert_eq!(
+			format!("{stealcell:#?}"),
+			"StealCell {\n    value: Some(\n        12,\n    ),\n}"
+		);
+
+		let stolen = stealcell.steal();
+		assert_eq!(format!("{stealcell:?}"), "StealCell(<stolen>)");
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn displays_the_value_or_stolen() {
+		let mut stealcell = StealCell::<f32>::new(1.5);
+		assert_eq!(format!("{stealcell}"), "1.5");
+		assert_eq!(format!("{stealcell:>6.2}"), "  1.50");
+
+		let stolen = stealcell.steal();
+		assert_eq!(format!("{stealcell}"), STOLEN_MARKER);
+		assert_eq!(format!("{stolen:.1}"), "1.5");
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn alternate_debug_includes_the_label_and_where_it_was_stolen() {
+		let mut stealcell = StealCell::<usize>::new_named(12, "player.inventory");
+		let (stolen, line) = (stealcell.steal(), line!());
+		let debug = format!("{stealcell:#?}");
+		let prefix = format!(
+			"StealCell {{\n    value: <stolen>,\n    label: \"player.inventory\",\n    stolen_at: {}:{line}:",
+			file!()
+		);
+		assert!(debug.starts_with(&prefix), "{debug}");
+		assert!(debug.ends_with(",\n}"), "{debug}");
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	#[cfg(not(feature = "no_std"))]
+	fn steal_catch_returns_the_value_when_f_returns() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let result = stealcell.steal_catch(|value| {
+			*value += 1;
+			*value * 10
+		});
+		assert_eq!(result.ok(), Some(20));
+		assert_eq!(stealcell, 2);
+	}
+
+	#[test]
+	#[cfg(not(feature = "no_std"))]
+	fn steal_catch_returns_the_value_when_f_panics() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(|_| {}));
+		let result = stealcell.steal_catch(|value| {
+			*value += 1;
+			panic!("the mod callback failed");
+		});
+		std::panic::set_hook(hook);
+
+		let payload = result.expect_err("expected a panic");
+		assert_eq!(
+			payload.downcast_ref::<&str>(),
+			Some(&"the mod callback failed")
+		);
+		assert!(!stealcell.is_stolen());
+		assert_eq!(stealcell, 2);
+	}
+
+	#[test]
+	fn with_stolen_returns_the_value() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let mut other = StealCell::<usize>::new(10);
+		let result = stealcell.with_stolen(|stolen| {
+			*stolen.get_mut() += *other.get_mut().unwrap();
+			assert_eq!(stolen.label(), None);
+			*stolen.get() * 2
+		});
+		assert_eq!(result, 22);
+		assert_eq!(stealcell, 11);
+	}
+
+	#[test]
+	fn with_stolen_returns_the_value_when_f_panics() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let message = panic_message(|| {
+			stealcell.with_stolen(|stolen| {
+				*stolen.get_mut() += 1;
+				panic!("the mod callback failed");
+			});
+		});
+		assert_eq!(message, "the mod callback failed");
+		assert!(!stealcell.is_stolen());
+		assert_eq!(stealcell, 2);
+	}
+
+	#[test]
+	fn with_stolen_try_returns_the_value_on_success_and_on_error() {
+		let mut stealcell = StealCell::new("12".to_string());
+		let parsed = stealcell.with_stolen_try(|value| {
+			value.push('3');
+			value.parse::<usize>()
+		});
+		assert_eq!(parsed, Ok(123));
+		assert_eq!(stealcell.get().map(String::as_str), Some("123"));
+
+		let parsed = stealcell.with_stolen_try(|value| {
+			value.push('x');
+			value.parse::<usize>()
+		});
+		assert_eq!(parsed, "123x".parse::<usize>());
+		assert!(!stealcell.is_stolen());
+		assert_eq!(stealcell.get().map(String::as_str), Some("123x"));
+	}
+
+	#[test]
+	fn with_stolen_try_returns_the_value_when_f_panics() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let message = panic_message(|| {
+			let _ = stealcell.with_stolen_try(|value| -> Result<(), ()> {
+				*value += 1;
+				panic!("the mod callback failed");
+			});
+		});
+		assert_eq!(message, "the mod callback failed");
+		assert_eq!(stealcell, 2);
+	}
+
+	#[test]
+	fn lend_returns_the_handed_back_value() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let result = stealcell.lend(|stolen| {
+			let stolen = stolen.map(|value| value + 1);
+			let doubled = *stolen.get() * 2;
+			(stolen, doubled)
+		});
+		assert_eq!(result, 4);
+		assert_eq!(stealcell, 2);
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, not(feature = "release_disarm")))]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn lend_panics_when_the_value_is_dropped() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let message = panic_message(|| {
+			stealcell.lend(|stolen| {
+				drop(stolen);
+				unreachable!("dropping the stolen value panics")
+			})
+		});
+		assert!(message.starts_with("You've lost a stolen value without returning it first!"));
+		assert!(stealcell.is_stolen());
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn with_stolen_panics_when_already_stolen() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		let message = panic_message(|| stealcell.with_stolen(|_| unreachable!()));
+		assert!(message.starts_with("value already stolen from: usize"));
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn cells_and_stolen_values_are_unwind_safe() {
+		fn assert_unwind_safe<T: std::panic::UnwindSafe + std::panic::RefUnwindSafe>() {}
+		assert_unwind_safe::<StealCell<usize>>();
+		assert_unwind_safe::<Stolen<usize>>();
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn messages_name_the_value_type_without_module_paths() {
+		let mut stealcell = StealCell::<Vec<Thing>>::new(Vec::new());
+		let stolen = stealcell.steal();
+		assert!(
+			panic_message(|| {
+				let _ = stealcell.steal();
+			})
+			.starts_with("value already stolen from: Vec<Thing>, first stolen at ")
+		);
+		assert!(
+			panic_message(|| {
+				stealcell.as_ref();
+			})
+			.starts_with("value already stolen from: Vec<Thing>, first stolen at ")
+		);
+		assert!(
+			panic_message(|| {
+				stealcell.as_mut();
+			})
+			.starts_with("value already stolen from: Vec<Thing>, first stolen at ")
+		);
+		stealcell.return_stolen(stolen);
+		assert_eq!(
+			panic_message(|| stealcell.return_stolen(StealCell::new(Vec::new()).steal())),
+			"trying to return a stolen value, but it was stolen from a different cell! Vec<Thing>"
+		);
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn reports_where_it_was_first_stolen() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let (stolen, line) = (stealcell.steal(), line!());
+		let message = panic_message(|| {
+			let _ = stealcell.steal();
+		});
+		assert!(message.contains(&format!(", first stolen at {}:{line}:", file!())));
+
+		let Err(StealError::AlreadyStolen { stolen_at, .. }) = stealcell.try_steal() else {
+			panic!("expected the value to be stolen");
+		};
+		assert_eq!(stolen_at.map(|location| location.line()), Some(line));
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	#[cfg(not(feature = "slim_panics"))]
+	fn try_steal_errors_when_already_stolen() {
+		let mut stealcell = StealCell::<Thing>::default();
+		let stolen = stealcell.try_steal().unwrap();
+		assert_eq!(stolen.as_ref().value, 99);
+
+		assert!(matches!(
+			stealcell.try_steal(),
+			Err(StealError::AlreadyStolen { type_name, .. }) if type_name.contains("Thing")
+		));
+
+		stealcell.return_stolen(stolen);
+		let stolen_again = stealcell.try_steal().unwrap();
+		stealcell.return_stolen(stolen_again);
+	}
+
+	#[test]
+	fn get_returns_none_while_stolen() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(stealcell.get(), Some(&1));
+		assert_eq!(stealcell.get_mut(), Some(&mut 1));
+
+		let stolen = stealcell.steal();
+		assert_eq!(stealcell.get(), None);
+		assert_eq!(stealcell.get_mut(), None);
+
+		stealcell.return_stolen(stolen);
+		*stealcell.get_mut().unwrap() = 2;
+		assert_eq!(stealcell.get(), Some(&2));
+	}
+
+	#[test]
+	fn as_option_works_with_option_combinators() {
+		let mut a = StealCell::<usize>::new(2);
+		let b = StealCell::<usize>::new(3);
+
+		let product = a.as_option().zip(b.as_option()).map(|(a, b)| a * b);
+		assert_eq!(product, Some(6));
+		assert_eq!(a.as_option().filter(|a| **a > 2), None);
+
+		if let Some(a) = a.as_option_mut().filter(|a| **a == 2) {
+			*a = 4;
+		}
+		assert_eq!(a.as_option().copied(), Some(4));
+		assert!(!a.is_stolen());
+
+		let stolen = a.steal();
+		assert_eq!(a.as_option().zip(b.as_option()), None);
+		assert_eq!(a.as_option_mut().map(|a| *a), None);
+		a.return_stolen(stolen);
+	}
+
+	#[test]
+	fn into_inner_hands_over_the_value() {
+		assert_eq!(StealCell::<usize>::new(1).into_inner(), Some(1));
+
+		let mut stealcell = StealCell::<usize>::new(1);
+		let mut stolen = stealcell.steal();
+		assert_eq!(stealcell.into_inner(), None);
+		assert_eq!(stolen.value.take(), Some(1)); // Disarming for the test
+	}
+
+	#[test]
+	fn take_removes_the_value_for_good() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(stealcell.take(), Some(1));
+		assert!(stealcell.is_stolen());
+		assert_eq!(stealcell.take(), None);
+	}
+
+	#[test]
+	fn take_returns_none_while_stolen() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		assert_eq!(stealcell.take(), None);
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell.take(), Some(1));
+	}
+
+	#[test]
+	fn take_if_takes_when_the_predicate_holds() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(stealcell.take_if(|value| *value == 1), Some(1));
+		assert!(stealcell.is_stolen());
+	}
+
+	#[test]
+	fn take_if_leaves_the_value_when_the_predicate_fails() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(
+			stealcell.take_if(|value| {
+				*value += 1;
+				false
+			}),
+			None
+		);
+		assert_eq!(stealcell.get(), Some(&2));
+	}
+
+	#[test]
+	fn take_if_does_not_call_the_predicate_when_stolen() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		assert_eq!(stealcell.take_if(|_| panic!("should not be called")), None);
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn steal_if_steals_when_the_predicate_holds() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal_if(|value| *value == 1).unwrap();
+		assert!(stealcell.is_stolen());
+		assert_eq!(*stolen.as_ref(), 1);
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn steal_if_leaves_the_value_when_the_predicate_fails() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert!(stealcell.steal_if(|value| *value == 2).is_none());
+		assert!(!stealcell.is_stolen());
+	}
+
+	#[test]
+	fn steal_if_returns_none_when_stolen() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		assert!(
+			stealcell
+				.steal_if(|_| panic!("should not be called"))
+				.is_none()
+		);
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn return_stolen_with_transforms_the_value() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		stealcell.return_stolen_with(stolen, |value| value + 1);
+		assert_eq!(stealcell.get(), Some(&2));
+	}
+
+	#[test]
+	fn return_stolen_with_leaves_the_cell_empty_when_the_closure_panics() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		let message = panic_message(|| {
+			stealcell.return_stolen_with(stolen, |_| panic!("transformation failed"));
+		});
+		assert_eq!(message, "transformation failed");
+		assert!(stealcell.is_stolen());
+	}
+
+	#[test]
+	#[should_panic]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn return_stolen_with_panics_when_occupied() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		mute_panic(|| stealcell.return_stolen_with(Stolen::new(2, None, None), |value| value));
+	}
+
+	#[test]
+	fn map_in_place_transforms_the_value() {
+		let mut stealcell = StealCell::new(vec![1]);
+		stealcell.map_in_place(|mut value| {
+			value.push(2);
+			value
+		});
+		assert_eq!(stealcell.get(), Some(&vec![1, 2]));
+	}
+
+	#[test]
+	fn map_in_place_leaves_the_cell_empty_when_the_closure_panics() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let message = panic_message(|| {
+			stealcell.map_in_place(|_| panic!("transformation failed"));
+		});
+		assert_eq!(message, "transformation failed");
+		assert!(stealcell.is_stolen());
+
+		let Err(
+			StealError::AlreadyStolen { stolen_at, .. } | StealError::Poisoned { stolen_at, .. },
+		) = stealcell.try_steal()
+		else {
+			panic!("expected the cell to be empty");
+		};
+		let diagnostics = cfg!(any(debug_assertions, feature = "diagnostics"));
+		assert_eq!(
+			stolen_at.map(|at| at.file()),
+			diagnostics.then_some(file!())
+		);
+		stealcell.set(2).unwrap();
+		assert_eq!(stealcell.get(), Some(&2));
+	}
+
+	#[test]
+	fn try_map_in_place_consumes_the_value_on_errors() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(
+			stealcell.try_map_in_place(|value| value.checked_add(1).ok_or(())),
+			Ok(())
+		);
+		assert_eq!(stealcell.get(), Some(&2));
+
+		assert_eq!(
+			stealcell.try_map_in_place(|value| value.checked_sub(3).ok_or("underflow")),
+			Err("underflow")
+		);
+		assert!(stealcell.is_stolen());
+		assert!(matches!(
+			stealcell.try_steal(),
+			Err(StealError::AlreadyStolen {
+				stolen_at: None,
+				..
+			})
+		));
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	fn map_in_place_panics_when_stolen() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		let message = panic_message(|| stealcell.map_in_place(|value| value + 1));
+		assert!(message.starts_with("value already stolen from: usize"));
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell.get(), Some(&1));
+	}
+
+	#[test]
+	fn steal_clone_leaves_the_value_in_the_cell() {
+		let mut stealcell = StealCell::new(vec![1]);
+		let mut clone = stealcell.steal_clone().unwrap();
+		clone.push(2);
+		assert!(!stealcell.is_stolen());
+		assert_eq!(stealcell.get(), Some(&vec![1]));
+
+		stealcell.as_mut().push(3);
+		assert_eq!(clone, vec![1, 2]);
+	}
+
+	#[test]
+	fn steal_clone_is_none_when_stolen() {
+		let mut stealcell = StealCell::new(vec![1]);
+		let stolen = stealcell.steal();
+		assert_eq!(stealcell.steal_clone(), None);
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn steal_with_snapshot_leaves_a_clone_to_read() {
+		let mut stealcell = StealCell::new(vec![1]);
+		let mut stolen = stealcell.steal_with_snapshot();
+		stolen.get_mut().push(2);
+		assert!(!stealcell.is_stolen());
+		assert_eq!(stealcell.get(), Some(&vec![1]));
+
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell.get(), Some(&vec![1, 2]));
+		let stolen = stealcell.steal();
+		assert_eq!(stolen.get(), &vec![1, 2]);
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	#[should_panic]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn steal_with_snapshot_panics_when_stolen() {
+		let mut stealcell = StealCell::<Vec<usize>>::empty();
+		mute_panic(|| stealcell.steal_with_snapshot().discard());
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	fn copies_the_value_out() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(stealcell.copied(), Some(1));
+		assert_eq!(stealcell.get_copy(), 1);
+
+		let stolen = stealcell.steal();
+		assert_eq!(stolen.copied(), 1);
+		assert_eq!(stealcell.copied(), None);
+		let message = panic_message(|| {
+			stealcell.get_copy();
+		});
+		assert!(message.starts_with("value already stolen from: usize"));
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell.get_copy(), 1);
+	}
+
+	#[test]
+	fn iterates_over_present_values() {
+		let mut cells = [
+			StealCell::<usize>::new(1),
+			StealCell::<usize>::new(2),
+			StealCell::<usize>::new(3),
+		];
+		let stolen = cells[1].steal();
+
+		let present: Vec<_> = cells.iter().flat_map(StealCell::iter).copied().collect();
+		assert_eq!(present, [1, 3]);
+		for value in cells.iter_mut().flatten() {
+			*value *= 10;
+		}
+		let [first, second, third] = &cells;
+		assert_eq!(first.iter().chain(second).chain(third).sum::<usize>(), 40);
+
+		cells[1].return_stolen(stolen);
+		let values: Vec<_> = cells.into_iter().flatten().collect();
+		assert_eq!(values, [10, 2, 30]);
+	}
+
+	#[test]
+	fn map_cell_maps_present_values() {
+		let stealcell = StealCell::new(12_u8);
+		let mapped: StealCell<String> = stealcell.map_cell(|value| value.to_string());
+		assert_eq!(mapped.get().map(String::as_str), Some("12"));
+	}
+
+	#[test]
+	fn map_cell_keeps_empty_cells_empty() {
+		let mut called = false;
+		let mapped = StealCell::<u8>::empty().map_cell(|value| {
+			called = true;
+			value.to_string()
+		});
+		assert!(mapped.is_stolen());
+		assert!(!called);
+	}
+
+	#[test]
+	fn try_map_cell_maps_present_values() {
+		let stealcell = StealCell::new("12");
+		let mapped = stealcell.try_map_cell(|value| value.parse::<usize>());
+		assert_eq!(mapped.unwrap().get(), Some(&12));
+	}
+
+	#[test]
+	fn try_map_cell_propagates_errors() {
+		let stealcell = StealCell::new("twelve");
+		assert!(
+			stealcell
+				.try_map_cell(|value| value.parse::<usize>())
+				.is_err()
+		);
+	}
+
+	#[test]
+	fn try_map_cell_keeps_stolen_cells_stolen() {
+		let stealcell = StealCell::<&str>::empty();
+		let mapped = stealcell.try_map_cell(|value| value.parse::<usize>());
+		assert!(mapped.unwrap().is_stolen());
+	}
+
+	#[test]
+	fn set_fills_an_empty_cell() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let mut stolen = stealcell.steal();
+		stolen.value.take(); // Lost for good, disarming for the test
+
+		assert_eq!(stealcell.set(2), Ok(()));
+		assert_eq!(stealcell.get(), Some(&2));
+	}
+
+	#[test]
+	fn set_hands_back_the_value_when_occupied() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(stealcell.set(2), Err(2));
+		assert_eq!(stealcell.get(), Some(&1));
+	}
+
+	#[test]
+	fn replace_returns_the_previous_value() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(stealcell.replace(2), Some(1));
+		assert_eq!(stealcell.get(), Some(&2));
+	}
+
+	#[test]
+	fn replace_fills_the_hole_of_a_stolen_cell() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		assert_eq!(stealcell.replace(2), None);
+		assert_eq!(stealcell.get(), Some(&2));
+
+		let Err(ReturnError::CellOccupied(mut stolen)) = stealcell.try_return_stolen(stolen) else {
+			panic!("expected the cell to be occupied");
+		};
+		stolen.value.take(); // Disarming for the test
+	}
+
+	#[test]
+	#[should_panic]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_when_returning_after_replace() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		stealcell.replace(2);
+		mute_panic(|| stealcell.return_stolen(stolen));
+	}
+
+	#[test]
+	fn transfers_into_an_empty_cell() {
+		let mut from = StealCell::<usize>::new(1);
+		let mut to = StealCell::<usize>::empty();
+		assert_eq!(from.transfer_to(&mut to), Ok(()));
+		assert!(from.is_stolen());
+		assert_eq!(to.get(), Some(&1));
+		assert_eq!(from.set(2), Ok(()));
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn transfer_makes_earlier_steals_stale() {
+		let mut from = StealCell::<usize>::new(1);
+		let mut to = StealCell::<usize>::new(2);
+		let stolen = to.steal();
+		from.transfer_to(&mut to).unwrap();
+		to.take();
+
+		let Err(ReturnError::Stale(stolen)) = to.try_return_stolen(stolen) else {
+			panic!("expected the value to be stale");
+		};
+		stolen.discard();
+	}
+
+	#[test]
+	fn transfer_fails_from_an_empty_cell() {
+		let mut from = StealCell::<usize>::new(1);
+		let mut to = StealCell::<usize>::empty();
+		let stolen = from.steal();
+
+		let Err(TransferError::SourceStolen(StealError::AlreadyStolen { stolen_at, .. })) =
+			from.transfer_to(&mut to)
+		else {
+			panic!("expected the source to be stolen");
+		};
+		assert_eq!(
+			stolen_at.is_some(),
+			cfg!(any(debug_assertions, feature = "diagnostics"))
+		);
+		assert!(to.is_stolen());
+		from.return_stolen(stolen);
+	}
+
+	#[test]
+	#[cfg(not(feature = "slim_panics"))]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn transfer_fails_into_an_occupied_cell() {
+		let mut from = StealCell::<usize>::new(1);
+		let mut to = StealCell::<usize>::new_named(2, "slot");
+		assert_eq!(
+			from.transfer_to(&mut to),
+			Err(TransferError::DestinationOccupied {
+				type_name: "usize",
+				label: Some("slot"),
+			})
+		);
+		assert_eq!(from.get(), Some(&1));
+		assert_eq!(to.get(), Some(&2));
+	}
+
+	#[test]
+	fn swaps_occupied_cells() {
+		let mut a = StealCell::<usize>::new(1);
+		let mut b = StealCell::<usize>::new(2);
+		a.swap(&mut b);
+		assert_eq!(a.get(), Some(&2));
+		assert_eq!(b.get(), Some(&1));
+		assert_eq!(a.try_swap(&mut b), Ok(()));
+		assert_eq!(a.get(), Some(&1));
+	}
+
+	#[test]
+	fn swap_fails_with_empty_cells() {
+		let mut a = StealCell::<usize>::new(1);
+		let mut b = StealCell::<usize>::new(2);
+
+		let stolen_a = a.steal();
+		let Err(SwapError::ThisStolen(StealError::AlreadyStolen { stolen_at, .. })) =
+			a.try_swap(&mut b)
+		else {
+			panic!("expected this cell to be stolen");
+		};
+		assert_eq!(
+			stolen_at.is_some(),
+			cfg!(any(debug_assertions, feature = "diagnostics"))
+		);
+		assert!(matches!(b.try_swap(&mut a), Err(SwapError::OtherStolen(_))));
+
+		let stolen_b = b.steal();
+		assert!(matches!(
+			a.try_swap(&mut b),
+			Err(SwapError::BothStolen(
+				StealError::AlreadyStolen { .. },
+				StealError::AlreadyStolen { .. }
+			))
+		));
+
+		a.return_stolen(stolen_a);
+		b.return_stolen(stolen_b);
+		assert_eq!(a.get(), Some(&1));
+		assert_eq!(b.get(), Some(&2));
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn swap_keeps_earlier_steals_stale() {
+		let mut a = StealCell::<usize>::new(1);
+		let mut b = StealCell::<usize>::new(2);
+		let stolen = a.steal();
+		a.set(3).unwrap();
+		a.swap(&mut b);
+		a.take();
+
+		let Err(ReturnError::Stale(stolen)) = a.try_return_stolen(stolen) else {
+			panic!("expected the value to be stale");
+		};
+		stolen.discard();
+	}
+
+	#[test]
+	#[should_panic(expected = "can't swap into an empty cell")]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	fn panics_when_swapping_with_an_empty_cell() {
+		let mut a = StealCell::<usize>::new(1);
+		let mut b = StealCell::<usize>::empty();
+		a.swap(&mut b);
+	}
+
+	#[test]
+	fn steal_replace_leaves_the_placeholder() {
+		let mut stealcell = StealCell::<String>::new("real".to_string());
+		let mut stolen = stealcell.steal_replace("placeholder".to_string());
+		assert!(!stealcell.is_stolen());
+		assert_eq!(stealcell.status(), CellState::Present);
+		assert_eq!(stealcell.as_ref(), "placeholder");
+		assert_eq!(stolen.get(), "real");
+
+		stolen.get_mut().push('!');
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell.as_ref(), "real!");
+
+		let stolen = stealcell.steal();
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell.as_ref(), "real!");
+	}
+
+	#[test]
+	fn steal_replace_placeholder_leaves_the_placeholder() {
+		let mut stealcell = StealCell::new(Some(1));
+		let stolen = stealcell.steal_replace_placeholder();
+		assert_eq!(stealcell.as_ref(), &None);
+		assert_eq!(stealcell.return_and_take_placeholder(stolen), Some(None));
+		assert_eq!(stealcell.as_ref(), &Some(1));
+	}
+
+	#[test]
+	fn returns_hand_back_the_placeholder() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal_replace(0);
+		*stealcell.as_mut() += 10;
+		assert_eq!(stealcell.return_and_take_placeholder(stolen), Some(10));
+		assert_eq!(stealcell.get(), Some(&1));
+
+		let stolen = stealcell.steal();
+		assert_eq!(stealcell.return_and_take_placeholder(stolen), None);
+		assert_eq!(stealcell.get(), Some(&1));
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn steal_replace_fails_once_the_placeholder_is_replaced() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal_replace(0);
+		assert_eq!(stealcell.replace(2), Some(0));
+
+		let Err(ReturnError::CellOccupied(stolen)) = stealcell.try_return_stolen(stolen) else {
+			panic!("expected the cell to be occupied");
+		};
+		stealcell.take();
+		let Err(ReturnError::Stale(stolen)) = stealcell.try_return_stolen(stolen) else {
+			panic!("expected the value to be stale");
+		};
+		stolen.discard();
+	}
+
+	#[test]
+	fn steal_replace_returns_once_the_placeholder_is_taken() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal_replace(0);
+		assert_eq!(stealcell.take(), Some(0));
+		assert!(stealcell.is_stolen());
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell.get(), Some(&1));
+	}
+
+	#[test]
+	fn get_or_insert_with_only_refills_empty_cells() {
+		let mut calls = 0;
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(
+			*stealcell.get_or_insert_with(|| {
+				calls += 1;
+				2
+			}),
+			1
+		);
+		assert_eq!(calls, 0);
+
+		stealcell.take();
+		for _ in 0..3 {
+			*stealcell.get_or_insert_with(|| {
+				calls += 1;
+				2
+			}) += 1;
+		}
+		assert_eq!(calls, 1);
+		assert_eq!(stealcell.get(), Some(&5));
+	}
+
+	#[test]
+	fn steal_or_default_steals_the_present_value() {
+		let mut stealcell = StealCell::<Thing>::new(Thing { value: 1 });
+		let stolen = stealcell.steal_or_default();
+		assert!(stealcell.is_stolen());
+		assert_eq!(stolen.as_ref().value, 1);
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn steal_or_default_defaults_when_stolen() {
+		let mut stealcell = StealCell::<Thing>::new(Thing { value: 1 });
+		let mut stolen = stealcell.steal();
+		stolen.value.take(); // Lost for good, disarming for the test
+
+		let defaulted = stealcell.steal_or_default();
+		assert!(stealcell.is_stolen());
+		assert_eq!(defaulted.as_ref().value, 99);
+		stealcell.return_stolen(defaulted);
+		assert_eq!(stealcell.as_ref().value, 99);
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn expect_steal_prefixes_the_panic_message() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		let message = panic_message(|| {
+			let _ = stealcell.expect_steal("world.thing");
+		});
+		assert!(message.starts_with("world.thing: value already stolen from:"));
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	fn expect_ref_and_mut_prefix_the_panic_message() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(*stealcell.expect_ref("world.thing"), 1);
+		*stealcell.expect_mut("world.thing") = 2;
+		assert_eq!(*stealcell.expect_ref("world.thing"), 2);
+
+		let stolen = stealcell.steal();
+		let message = panic_message(|| {
+			stealcell.expect_ref("world.thing");
+		});
+		assert!(message.starts_with("world.thing: value already stolen from:"));
+		let message = panic_message(|| {
+			stealcell.expect_mut("world.other_thing");
+		});
+		assert!(message.starts_with("world.other_thing: value already stolen from:"));
+		stealcell.expect_return_stolen(stolen, "world.thing");
+	}
+
+	#[test]
+	fn modify_hands_back_the_result() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let previous = stealcell.modify(|value| core::mem::replace(value, 2));
+		assert_eq!(previous, 1);
+		assert_eq!(stealcell.try_modify(|value| *value * 10), Ok(20));
+		assert_eq!(stealcell.get(), Some(&2));
+		assert!(!stealcell.is_stolen());
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	fn modify_fails_when_stolen() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		let mut called = false;
+
+		let Err(StealError::AlreadyStolen { stolen_at, .. }) =
+			stealcell.try_modify(|_| called = true)
+		else {
+			panic!("expected the value to be stolen");
+		};
+		assert_eq!(
+			stolen_at.is_some(),
+			cfg!(any(debug_assertions, feature = "diagnostics"))
+		);
+		let message = panic_message(|| stealcell.modify(|_| called = true));
+		assert!(message.starts_with("value already stolen from: usize"));
+		assert!(!called);
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn expect_return_stolen_prefixes_the_panic_message() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let message = panic_message(|| {
+			stealcell.expect_return_stolen(Stolen::new(2, None, None), "world.thing");
+		});
+		assert!(message.starts_with("world.thing: trying to return a stolen value"));
+	}
+
+	#[test]
+	fn empty_starts_out_stolen() {
+		let mut stealcell = StealCell::<usize>::empty();
+		assert!(stealcell.is_stolen());
+		assert_eq!(stealcell.get(), None);
+		assert_eq!(stealcell.set(1), Ok(()));
+		assert!(!stealcell.is_stolen());
+		assert_eq!(*stealcell.as_ref(), 1);
+	}
+
+	#[test]
+	#[should_panic]
+	fn panics_when_reading_empty() {
+		let stealcell = StealCell::<usize>::empty();
+		mute_panic(|| {
+			stealcell.as_ref();
+		});
+	}
+
+	#[test]
+	fn converts_from_and_into_options() {
+		let stealcell: StealCell<usize> = 1.into();
+		assert_eq!(stealcell.get(), Some(&1));
+
+		let stealcell = StealCell::<usize>::from(Some(1));
+		assert!(!stealcell.is_stolen());
+		assert_eq!(Option::from(stealcell), Some(1));
+
+		let stealcell = StealCell::<usize>::from(None);
+		assert!(stealcell.is_stolen());
+		assert_eq!(Option::<usize>::from(stealcell), None);
+
+		let cells: Vec<StealCell<usize>> = [Some(1), None].into_iter().map(Into::into).collect();
+		assert!(!cells[0].is_stolen());
+		assert!(cells[1].is_stolen());
+	}
+
+	#[test]
+	fn contains_checks_the_present_value() {
+		let mut stealcell = StealCell::<String>::new("thing".to_string());
+		assert!(stealcell.contains(&"thing"));
+		assert!(!stealcell.contains(&"other"));
+
+		let stolen = stealcell.steal();
+		assert!(!stealcell.contains(&"thing"));
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn value_eq_never_matches_stolen_cells() {
+		let mut a = StealCell::<usize>::new(1);
+		let mut b = StealCell::<usize>::new(1);
+		assert!(a.value_eq(&b));
+		assert!(!a.value_eq(&StealCell::new(2)));
+
+		let stolen_a = a.steal();
+		assert!(!a.value_eq(&b));
+		assert!(!b.value_eq(&a));
+
+		let stolen_b = b.steal();
+		assert!(!a.value_eq(&b));
+
+		a.return_stolen(stolen_a);
+		b.return_stolen(stolen_b);
+	}
+
+	#[test]
+	fn stolen_cells_are_never_equal() {
+		let present = StealCell::<usize>::new(1);
+		let mut stolen_cell = StealCell::<usize>::new(1);
+		let stolen = stolen_cell.steal();
+		let empty = StealCell::<usize>::empty();
+
+		assert!(present == StealCell::new(1));
+		assert!(present != StealCell::new(2));
+		assert!(present != stolen_cell);
+		assert!(stolen_cell != present);
+		assert!(stolen_cell != empty);
+		assert!(empty != StealCell::empty());
+
+		stolen_cell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn option_eq_compares_like_options() {
+		let present = StealCell::<usize>::new(1);
+		let mut stolen_cell = StealCell::<usize>::new(1);
+		let stolen = stolen_cell.steal();
+		let empty = StealCell::<usize>::empty();
+
+		assert!(present.option_eq(&StealCell::new(1)));
+		assert!(!present.option_eq(&StealCell::new(2)));
+		assert!(!present.option_eq(&stolen_cell));
+		assert!(!stolen_cell.option_eq(&present));
+		assert!(stolen_cell.option_eq(&empty));
+		assert!(empty.option_eq(&empty));
+
+		stolen_cell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn status_reports_the_cell_state() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		assert_eq!(stealcell.status(), CellState::Present);
+		let stolen = stealcell.steal();
+		assert_eq!(stealcell.status(), CellState::Stolen);
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell.status(), CellState::Present);
+	}
+
+	#[test]
+	fn cell_state_displays() {
+		assert_eq!(CellState::Present.to_string(), "present");
+		assert_eq!(CellState::Stolen.to_string(), "stolen");
+	}
+
+	#[test]
+	fn compares_with_values_and_stolen_values() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let other = StealCell::<usize>::new(1);
+		assert_eq!(stealcell, 1);
+		assert_ne!(stealcell, 2);
+
+		let stolen = stealcell.steal();
+		assert_ne!(stealcell, 1);
+		assert_eq!(stolen, 1);
+		assert_ne!(stolen, 2);
+		assert_eq!(other, stolen);
+		assert_eq!(stolen, other);
+		assert_ne!(stealcell, stolen);
+		assert_ne!(stolen, stealcell);
+
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell, 1);
+	}
+
+	#[test]
+	fn compares_stolen_values() {
+		let mut a = StealCell::<usize>::new(1);
+		let mut b = StealCell::<usize>::new(1);
+		let mut c = StealCell::<usize>::new(2);
+		let (stolen_a, stolen_b, stolen_c) = (a.steal(), b.steal(), c.steal());
+		assert_eq!(stolen_a, stolen_b);
+		assert_ne!(stolen_a, stolen_c);
+		a.return_stolen(stolen_a);
+		b.return_stolen(stolen_b);
+		c.return_stolen(stolen_c);
+	}
+
+	fn increment(value: &mut impl BorrowMut<usize>) {
+		*value.borrow_mut() += 1;
+	}
+
+	#[test]
+	fn borrows() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		increment(&mut stealcell);
+		assert_eq!(*Borrow::<usize>::borrow(&stealcell), 2);
+
+		let mut stolen = stealcell.steal();
+		increment(&mut stolen);
+		assert_eq!(*Borrow::<usize>::borrow(&stolen), 3);
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell, 3);
+	}
+
+	#[test]
+	#[should_panic]
+	fn panics_when_borrowing_stolen() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let mut stolen = stealcell.steal();
+		stolen.value.take(); // Disarming for the test
+		mute_panic(|| increment(&mut stealcell));
+	}
+
+	#[test]
+	fn as_deref_reaches_the_target() {
+		let mut string = StealCell::new("thing".to_string());
+		let mut boxed = StealCell::new(Box::new(1_usize));
+		let mut vec = StealCell::new(vec![1_usize, 2]);
+		assert_eq!(string.as_deref(), Some("thing"));
+		assert_eq!(boxed.as_deref(), Some(&1));
+		assert_eq!(vec.as_deref(), Some([1, 2].as_slice()));
+
+		string.as_deref_mut().unwrap().make_ascii_uppercase();
+		*boxed.as_deref_mut().unwrap() += 1;
+		vec.as_deref_mut().unwrap().reverse();
+		assert_eq!(string.as_deref(), Some("THING"));
+		assert_eq!(boxed.as_deref(), Some(&2));
+		assert_eq!(vec.as_deref(), Some([2, 1].as_slice()));
+
+		let stolen = string.steal();
+		assert_eq!(string.as_deref(), None);
+		assert_eq!(string.as_deref_mut(), None);
+		string.return_stolen(stolen);
+	}
+
+	#[test]
+	fn inspect_only_calls_when_present() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let mut seen = None;
+		assert!(!stealcell.inspect(|value| seen = Some(*value)).is_stolen());
+		assert_eq!(seen, Some(1));
+
+		let stolen = stealcell.steal();
+		stealcell.inspect(|_| panic!("should not be called"));
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	fn defaults() {
+		let stealcell = StealCell::<Thing>::default();
+		assert_eq!(stealcell.as_ref().value, 99);
+	}
+
+	#[test]
+	#[cfg(not(feature = "no_std"))]
+	fn derefs() {
+		let stealcell = StealCell::<usize>::new(12);
+		assert_eq!(*stealcell, 12);
+	}
+
+	#[test]
+	#[cfg(not(feature = "no_std"))]
+	fn deref_muts() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		*stealcell = 12;
+		assert_eq!(*stealcell, 12);
+	}
+
+	#[test]
+	#[should_panic]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_on_unnecessary_return() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		mute_panic(|| stealcell.return_stolen(Stolen::new(12, None, None)));
+	}
+
+	#[test]
+	#[should_panic]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_when_returning_nothing() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let mut actual_stolen = stealcell.steal();
+		actual_stolen.value = None; // Disarming for the test
+
+		mute_panic(|| stealcell.return_stolen(actual_stolen));
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn try_return_stolen_hands_back_the_stolen_value_when_occupied() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		stealcell.set(2).unwrap();
+
+		let Err(ReturnError::CellOccupied(stolen)) = stealcell.try_return_stolen(stolen) else {
+			panic!("expected the cell to be occupied");
+		};
+		assert_eq!(*stolen.as_ref(), 1);
+		assert_eq!(*stealcell.as_ref(), 2);
+
+		// Still armed, but stale now that the cell was refilled
+		stealcell.take();
+		let Err(ReturnError::Stale(stolen)) = stealcell.try_return_stolen(stolen) else {
+			panic!("expected the value to be stale");
+		};
+		stealcell.return_stolen_unchecked(stolen);
+		assert_eq!(*stealcell.as_ref(), 1);
+	}
+
+	#[test]
+	fn try_return_stolen_errors_when_already_returned() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let mut actual_stolen = stealcell.steal();
+		actual_stolen.value = None; // Disarming for the test
+
+		assert!(matches!(
+			stealcell.try_return_stolen(actual_stolen),
+			Err(ReturnError::AlreadyReturned)
+		));
+		assert!(stealcell.is_stolen());
+	}
+
+	#[test]
+	fn returns_to_the_same_cell() {
+		let mut a = StealCell::<usize>::new(1);
+		let stolen = a.steal();
+		a.return_stolen(stolen);
+		let stolen = a.steal();
+		a.return_stolen(stolen);
+		assert_eq!(a, 1);
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn rejects_values_stolen_from_another_cell() {
+		let mut a = StealCell::<usize>::new(1);
+		let mut b = StealCell::<usize>::new(2);
+		let stolen_a = a.steal();
+		let stolen_b = b.steal();
+
+		let Err(ReturnError::WrongCell(stolen_a)) = b.try_return_stolen(stolen_a) else {
+			panic!("expected the value to be rejected");
+		};
+		let message = panic_message(|| b.return_stolen(stolen_a));
+		assert!(message.starts_with(
+			"trying to return a stolen value, but it was stolen from a different cell!"
+		));
+		assert!(a.is_stolen());
+
+		a.return_stolen_unchecked(stolen_b);
+		assert_eq!(a, 2);
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn rejects_stale_values_after_the_cell_was_refilled() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stale = stealcell.steal();
+		assert_eq!(stealcell.replace(2), None);
+		assert_eq!(stealcell.take(), Some(2));
+
+		let Err(ReturnError::Stale(stale)) = stealcell.try_return_stolen(stale) else {
+			panic!("expected the value to be stale");
+		};
+		let message = panic_message(|| stealcell.return_stolen(stale));
+		assert!(message.starts_with(
+			"trying to return a stolen value, but the cell was refilled since it was stolen!"
+		));
+		assert!(stealcell.is_stolen());
+	}
+
+	#[test]
+	fn values_stolen_after_a_refill_can_be_returned() {
+		let mut stealcell = StealCell::<usize>::empty();
+		*stealcell.get_or_insert_with(|| 1) += 1;
+		let stolen = stealcell.steal();
+		stealcell.return_stolen(stolen);
+		assert_eq!(stealcell, 2);
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn untraceable_values_can_be_returned_anywhere() {
+		let mut a = StealCell::<usize>::new(1);
+		let mut b = StealCell::<usize>::empty();
+		a.steal().map(|value| value + 1).return_to(&mut b);
+		assert_eq!(b, 2);
+
+		let mut pairs = StealCell::<(usize, usize)>::new((1, 2));
+		let mut other_pairs = StealCell::<(usize, usize)>::new((3, 4));
+		let (one, two) = pairs.steal().split();
+		let (three, four) = other_pairs.steal().split();
+		let mixed = Stolen::zip(one, four);
+		assert_eq!(mixed.origin, None);
+		mixed.return_to(&mut pairs);
+		Stolen::zip(three, two).return_to(&mut other_pairs);
+		assert_eq!(pairs, (1, 4));
+		assert_eq!(other_pairs, (3, 2));
+	}
+
+	#[test]
+	#[cfg(feature = "debug_thread_affinity")]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_when_returned_on_a_different_thread() {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let (sender, receiver) = std::sync::mpsc::channel::<Stolen<usize>>();
+		sender.send(stealcell.steal()).unwrap();
+
+		let hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(|_| {}));
+		let cell = &mut stealcell;
+		let result = std::thread::scope(|scope| {
+			scope
+				.spawn(move || cell.return_stolen(receiver.recv().unwrap()))
+				.join()
+		});
+		std::panic::set_hook(hook);
+
+		let payload = result.expect_err("expected a panic");
+		let message = payload.downcast_ref::<String>().unwrap();
+		assert!(message.starts_with(
+			"trying to return a stolen value on a different thread than it was stolen on! usize, stolen on ThreadId("
+		));
+		assert!(stealcell.is_stolen());
+
+		let stolen = stealcell.try_steal();
+		assert!(stolen.is_err());
+		stealcell.set(1).unwrap();
+		let stolen = stealcell.steal();
+		stealcell.return_stolen(stolen);
+	}
+
+	#[test]
+	#[cfg(not(any(
+		debug_assertions,
+		feature = "diagnostics",
+		feature = "poison",
+		feature = "deadline",
+		feature = "stats",
+		feature = "test_util",
+		feature = "salvage"
+	)))]
+	fn has_no_extra_fields_without_features() {
+		assert_eq!(
+			core::mem::size_of::<StealCell<u64>>(),
+			core::mem::size_of::<Option<u64>>()
+		);
+	}
+
+	#[cfg(feature = "stats")]
+	mod stats {
+		use crate::StealCell;
+
+		#[test]
+		fn counts_steals_and_returns() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			for _ in 0..10 {
+				let stolen = stealcell.steal();
+				stealcell.return_stolen(stolen);
+			}
+			let stolen = stealcell.try_steal().unwrap();
+			assert!(stealcell.try_steal().is_err());
+			assert_eq!(stealcell.steal_count(), 11);
+			assert_eq!(stealcell.return_count(), 10);
+
+			stealcell.try_return_stolen(stolen).unwrap();
+			assert_eq!(stealcell.return_count(), 11);
+		}
+
+		#[test]
+		fn resets_counts() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			let stolen = stealcell.steal();
+			stealcell.reset_counts();
+			assert_eq!(stealcell.steal_count(), 0);
+			stealcell.return_stolen(stolen);
+			assert_eq!(stealcell.steal_count(), 0);
+			assert_eq!(stealcell.return_count(), 1);
+		}
+
+		#[test]
+		fn does_not_count_failed_returns() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			let stolen = stealcell.steal();
+			stealcell.set(2).unwrap();
+			let Err(crate::ReturnError::CellOccupied(stolen)) = stealcell.try_return_stolen(stolen)
+			else {
+				panic!("expected the cell to be occupied");
+			};
+			assert_eq!(stealcell.return_count(), 0);
+			stealcell.take();
+			stealcell.return_stolen_unchecked(stolen);
+			assert_eq!(stealcell.return_count(), 1);
+		}
+	}
+
+	#[cfg(all(feature = "salvage", not(feature = "abort_on_loss")))]
+	mod salvage {
+		use std::sync::{Arc, Mutex};
+
+		use super::*;
+
+		#[test]
+		fn lost_values_are_salvaged() {
+			let salvaged = Arc::new(Mutex::new(Vec::new()));
+			let mut stealcell = StealCell::new("unflushed".to_string());
+			stealcell.on_loss({
+				let salvaged = salvaged.clone();
+				move |value| salvaged.lock().unwrap().push(value)
+			});
+
+			let stolen = stealcell.steal();
+			stealcell.return_stolen(stolen);
+			assert!(salvaged.lock().unwrap().is_empty());
+
+			lose(stealcell.steal());
+			assert_eq!(*salvaged.lock().unwrap(), ["unflushed"]);
+		}
+
+		#[test]
+		#[cfg(any(debug_assertions, not(feature = "release_disarm")))]
+		fn losing_a_salvaged_value_still_panics() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			stealcell.on_loss(|_| {});
+			let stolen = stealcell.steal();
+			let message = panic_message(|| drop(stolen));
+			assert!(message.starts_with("You've lost a stolen value without returning it first!"));
+		}
+
+		#[test]
+		fn stolen_values_keep_their_hook() {
+			let salvaged = Arc::new(Mutex::new(Vec::new()));
+			let mut stealcell = StealCell::<usize>::new(1);
+			stealcell.on_loss({
+				let salvaged = salvaged.clone();
+				move |value| salvaged.lock().unwrap().push(value)
+			});
+			let stolen = stealcell.steal();
+			stealcell.on_loss(|_| unreachable!("values stolen before keep the old hook"));
+			lose(stolen);
+			stealcell.set(2).unwrap();
+			lose(stealcell.steal().map(|value| value + 1));
+			assert_eq!(*salvaged.lock().unwrap(), [1]);
+		}
+	}
+
+	#[cfg(feature = "poison")]
+	mod poison {
+		use super::*;
+
+		#[test]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn losing_the_stolen_value_poisons_the_cell() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			let stolen = stealcell.steal();
+			assert!(!stealcell.is_poisoned());
+			lose(stolen);
+			assert!(stealcell.is_poisoned());
+			assert_eq!(stealcell.status(), CellState::Poisoned);
+			assert!(matches!(
+				stealcell.try_steal(),
+				Err(StealError::Poisoned { .. })
+			));
+			let message = panic_message(|| {
+				stealcell.as_ref();
+			});
+			assert!(message.starts_with("value was lost, cell is poisoned:"));
+		}
+
+		#[test]
+		fn panicking_while_mapping_in_place_poisons_the_cell() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			stealcell.map_in_place(|value| value + 1);
+			assert!(!stealcell.is_poisoned());
+
+			let _ = panic_message(|| stealcell.map_in_place(|_| panic!("transformation failed")));
+			assert!(stealcell.is_poisoned());
+			assert!(matches!(
+				stealcell.try_steal(),
+				Err(StealError::Poisoned { .. })
+			));
+		}
+
+		#[test]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn losing_a_mapped_value_poisons_the_cell() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			lose(stealcell.steal().map(|value| value + 1));
+			assert!(stealcell.is_poisoned());
+		}
+
+		#[test]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn losing_half_of_a_split_value_poisons_the_cell() {
+			let mut stealcell = StealCell::<(usize, usize)>::new((1, 2));
+			let (a, b) = stealcell.steal().split();
+			lose(a);
+			assert!(stealcell.is_poisoned());
+			b.discard();
+		}
+
+		#[test]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn refilling_recovers_a_poisoned_cell() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			let stolen = stealcell.steal();
+			lose(stolen);
+			assert!(stealcell.is_poisoned());
+
+			stealcell.set(2).unwrap();
+			assert!(!stealcell.is_poisoned());
+			let _ = stealcell.take();
+			assert_eq!(stealcell.status(), CellState::Stolen);
+			stealcell.set(2).unwrap();
+			let stolen = stealcell.steal();
+			assert!(!stealcell.is_poisoned());
+			stealcell.return_stolen(stolen);
+			assert_eq!(stealcell.status(), CellState::Present);
+		}
+
+		#[test]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn lost_stale_values_do_not_poison_refilled_cells() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			let stale = stealcell.steal();
+			stealcell.set(2).unwrap();
+			let stolen = stealcell.steal();
+			lose(stale);
+			assert!(!stealcell.is_poisoned());
+			stealcell.return_stolen(stolen);
+		}
+
+		#[test]
+		fn returning_does_not_poison() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			let stolen = stealcell.steal();
+			stealcell.return_stolen(stolen);
+			let _ = stealcell.take();
+			assert!(!stealcell.is_poisoned());
+			assert_eq!(stealcell.status(), CellState::Stolen);
+		}
+	}
+
+	mod stolen {
+
+		use super::*;
+		#[test]
+		#[should_panic]
+		#[cfg(any(debug_assertions, not(feature = "release_disarm")))]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn panics_when_dropped() {
+			let mut stealcell = StealCell::<Thing>::default();
+			let stolen = stealcell.steal();
+			mute_panic(|| drop(stolen));
+		}
+
+		#[test]
+		fn into_inner_keeps_the_value() {
+			let mut stealcell = StealCell::<Thing>::default();
+			let value = stealcell.steal().into_inner().value;
+			assert_eq!(value, 99);
+			assert!(stealcell.is_stolen());
+		}
+
+		#[test]
+		fn discard_drops_the_value_without_panicking() {
+			let mut stealcell = StealCell::<String>::new("corrupted".to_string());
+			stealcell.steal().discard();
+			assert!(stealcell.is_stolen());
+
+			assert_eq!(stealcell.set("fresh".to_string()), Ok(()));
+			let stolen = stealcell.steal();
+			assert_eq!(stolen.as_ref(), "fresh");
+			stealcell.return_stolen(stolen);
+			assert!(stealcell.contains(&"fresh"));
+		}
+
+		#[test]
+		fn into_cell_rehouses_the_value() {
+			let stolen = {
+				let mut old_home = StealCell::<usize>::new(12);
+				old_home.steal()
+			};
+
+			let mut new_home = stolen.into_cell();
+			assert_eq!(new_home.get(), Some(&12));
+			let stolen = new_home.steal();
+			new_home.return_stolen(stolen);
+			assert!(!new_home.is_stolen());
+		}
+
+		#[test]
+		fn debug_formats_the_stolen_value() {
+			let mut stealcell = StealCell::<Option<usize>>::new(Some(12));
+			let stolen = stealcell.steal();
+			assert_eq!(format!("{stolen:?}"), "Stolen(Some(12))");
+			assert_eq!(
+				format!("{stolen:#?}"),
+				"Stolen(\n    Some(\n        12,\n    ),\n)"
+			);
+			stealcell.return_stolen(stolen);
+		}
+
+		#[test]
+		fn splits_and_zips_pairs() {
+			let mut stealcell = StealCell::<(usize, String)>::new((1, "one".to_string()));
+			let (mut number, name) = stealcell.steal().split();
+			*number.as_mut() += 1;
+			assert_eq!(name.as_ref(), "one");
+			Stolen::zip(number, name).return_to(&mut stealcell);
+			assert_eq!(stealcell, (2, "one".to_string()));
+		}
+
+		#[test]
+		fn splits_and_zips_triples() {
+			let mut stealcell = StealCell::<(usize, usize, usize)>::new((1, 2, 3));
+			let (a, b, c) = stealcell.steal().split();
+			Stolen::zip3(c, b, a).return_to(&mut stealcell);
+			assert_eq!(stealcell, (3, 2, 1));
+		}
+
+		#[test]
+		#[should_panic]
+		#[cfg(any(debug_assertions, not(feature = "release_disarm")))]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn split_halves_stay_armed() {
+			let mut stealcell = StealCell::<(usize, usize)>::new((1, 2));
+			let (a, b) = stealcell.steal().split();
+			a.discard();
+			mute_panic(|| drop(b));
+		}
+
+		#[test]
+		fn replace_swaps_the_returned_value() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			let mut stolen = stealcell.steal();
+			assert_eq!(stolen.replace(2), 1);
+			assert_eq!(stolen, 2);
+			stealcell.return_stolen(stolen);
+			assert_eq!(stealcell, 2);
+		}
+
+		#[test]
+		fn get_and_get_mut_access_the_value() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			let mut stolen = stealcell.steal();
+			assert_eq!(*stolen.get(), 1);
+			*stolen.get_mut() = 2;
+			assert_eq!(*stolen.get(), 2);
+			stealcell.return_stolen(stolen);
+			assert_eq!(stealcell, 2);
+		}
+
+		#[test]
+		fn as_deref_reaches_the_target() {
+			let mut string = StealCell::new("thing".to_string());
+			let mut boxed = StealCell::new(Box::new(1_usize));
+			let mut vec = StealCell::new(vec![1_usize, 2]);
+			let mut stolen_string = string.steal();
+			let mut stolen_boxed = boxed.steal();
+			let mut stolen_vec = vec.steal();
+
+			stolen_string.as_deref_mut().make_ascii_uppercase();
+			*stolen_boxed.as_deref_mut() += 1;
+			stolen_vec.as_deref_mut().reverse();
+			assert_eq!(stolen_string.as_deref(), "THING");
+			assert_eq!(stolen_boxed.as_deref(), &2);
+			assert_eq!(stolen_vec.as_deref(), [2, 1].as_slice());
+
+			string.return_stolen(stolen_string);
+			boxed.return_stolen(stolen_boxed);
+			vec.return_stolen(stolen_vec);
+		}
+
+		#[test]
+		fn inspect_passes_the_stolen_value_through() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			let mut seen = None;
+			stealcell
+				.steal()
+				.inspect(|value| seen = Some(*value))
+				.return_to(&mut stealcell);
+			assert_eq!(seen, Some(1));
+			assert_eq!(stealcell, 1);
+		}
+
+		#[test]
+		fn swap_with_exchanges_values_with_another_cell() {
+			let mut a = StealCell::<usize>::new(1);
+			let mut b = StealCell::<usize>::new(2);
+			let mut stolen = a.steal();
+			stolen.swap_with(&mut b);
+			assert_eq!(stolen, 2);
+			assert_eq!(b, 1);
+			stolen.return_to(&mut a);
+			assert_eq!(a, 2);
+		}
+
+		#[test]
+		#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+		fn swap_with_panics_when_the_other_cell_is_stolen() {
+			let mut a = StealCell::<usize>::new(1);
+			let mut b = StealCell::<usize>::new(2);
+			let mut stolen_a = a.steal();
+			let stolen_b = b.steal();
+			let message = panic_message(|| stolen_a.swap_with(&mut b));
+			assert!(message.starts_with("value already stolen from:"));
+			a.return_stolen(stolen_a);
+			b.return_stolen(stolen_b);
+		}
+
+		#[test]
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		fn try_return_to_hands_back_the_stolen_value_when_occupied() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			let stolen = stealcell.steal();
+			stealcell.set(2).unwrap();
+
+			let stolen = stolen.try_return_to(&mut stealcell).unwrap_err();
+			assert_eq!(stolen, 1);
+			assert_eq!(stealcell, 2);
+
+			assert_eq!(stealcell.take(), Some(2));
+			let stolen = stolen.try_return_to(&mut stealcell).unwrap_err();
+			stealcell.return_stolen_unchecked(stolen);
+			assert_eq!(stealcell, 1);
+		}
+
+		#[test]
+		fn maps_into_another_type() {
+			let mut from = StealCell::<usize>::new(12);
+			let mut to = StealCell::<String>::empty();
+			let stolen = from.steal().map(|value| value.to_string());
+			assert_eq!(stolen.as_ref(), "12");
+			stolen.return_to(&mut to);
+			assert_eq!(to.get().map(String::as_str), Some("12"));
+			assert!(from.is_stolen());
+		}
+
+		#[test]
+		fn map_does_not_panic_twice_when_the_closure_panics() {
+			let mut stealcell = StealCell::<usize>::new(12);
+			let stolen = stealcell.steal();
+			let message = panic_message(|| {
+				let _ = stolen.map(|_| -> usize { panic!("mapping failed") });
+			});
+			assert_eq!(message, "mapping failed");
+		}
+
+		#[test]
+		fn returns_to_the_cell() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			stealcell.steal().return_to(&mut stealcell);
+			assert_eq!(stealcell.get(), Some(&1));
+		}
+
+		#[test]
+		#[should_panic]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn panics_when_returned_to_an_occupied_cell() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			mute_panic(|| Stolen::new(2, None, None).return_to(&mut stealcell));
+		}
+
+		#[test]
+		#[should_panic]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn panics_when_returning_nothing_to_the_cell() {
+			let mut stealcell = StealCell::<usize>::new(1);
+			let mut actual_stolen = stealcell.steal();
+			actual_stolen.value = None; // Disarming for the test
+
+			mute_panic(|| actual_stolen.return_to(&mut stealcell));
+		}
+
+		#[test]
+		#[cfg(any(debug_assertions, not(feature = "release_disarm")))]
+		#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+		#[cfg(not(feature = "abort_on_loss"))]
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		fn reports_the_label_of_the_cell_when_dropped() {
+			let mut stealcell = StealCell::new_named(Thing::default(), "player.inventory");
+			let (stolen, line) = (stealcell.steal(), line!());
+			assert_eq!(stolen.label(), Some("player.inventory"));
+			let message = panic_message(|| drop(stolen));
+			let expected = format!(
+				"You've lost a stolen value without returning it first! Thing stolen from 'player.inventory' at {}:{line}:",
+				file!(),
+			);
+			assert!(message.starts_with(&expected));
+		}
+
+		#[test]
+		#[cfg(any(debug_assertions, not(feature = "release_disarm")))]
+		#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn reports_where_it_was_stolen_when_dropped() {
+			let mut stealcell = StealCell::<Thing>::default();
+			let (stolen, line) = (stealcell.steal(), line!());
+			let message = panic_message(|| drop(stolen));
+			assert!(message.starts_with("You've lost a stolen value without returning it first!"));
+			assert!(message.contains("Thing stolen at"));
+			assert!(message.contains(&format!("{}:{line}:", file!())));
+		}
+
+		#[test]
+		#[cfg(all(
+			feature = "steal_timing",
+			any(debug_assertions, not(feature = "release_disarm"))
+		))]
+		#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn reports_how_long_ago_it_was_stolen_when_dropped() {
+			let mut stealcell = StealCell::<Thing>::default();
+			let stolen = stealcell.steal();
+			std::thread::sleep(core::time::Duration::from_millis(20));
+			let message = panic_message(|| drop(stolen));
+			let ago = message.split(' ').rev().nth(1).unwrap();
+			assert!(message.ends_with(" ago"));
+			assert!(ago.starts_with("00:00:00."));
+			assert!(ago["00:00:00.".len()..].parse::<u32>().unwrap() >= 20);
+		}
+
+		#[test]
+		fn formats_how_long_ago_it_was_stolen() {
+			let stolen_from = crate::StolenFrom {
+				label: Some("score"),
+				stolen_at: None,
+				ago: Some(core::time::Duration::from_millis(83_456)),
+			};
+			assert_eq!(
+				stolen_from.to_string(),
+				" stolen from 'score' 00:01:23.456 ago"
+			);
+			let stolen_from = crate::StolenFrom {
+				label: None,
+				stolen_at: None,
+				ago: Some(core::time::Duration::from_secs(3 * 3600 + 5)),
+			};
+			assert_eq!(stolen_from.to_string(), " stolen 03:00:05.000 ago");
+		}
+
+		#[test]
+		#[cfg(all(feature = "release_disarm", not(debug_assertions)))]
+		fn is_dropped_silently_in_release_builds() {
+			let mut stealcell = StealCell::<Thing>::default();
+			drop(stealcell.steal());
+			assert!(stealcell.is_stolen());
+		}
+
+		#[test]
+		#[cfg(not(feature = "no_std"))]
+		#[cfg(not(feature = "abort_on_loss"))]
+		fn does_not_panic_again_while_panicking() {
+			let mut stealcell = StealCell::<Thing>::default();
+			let message = panic_message(|| {
+				let _stolen = stealcell.steal();
+				panic!("the original panic");
+			});
+			assert_eq!(message, "the original panic");
+			assert!(stealcell.is_stolen());
 		}
 
 		#[test]
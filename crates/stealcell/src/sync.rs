@@ -0,0 +1,14 @@
+//! Cells that can be stolen from through shared references, to be shared
+//! between threads.
+
+mod atomic_steal_cell;
+#[cfg(not(feature = "no_std"))]
+mod return_ticket;
+#[cfg(not(feature = "no_std"))]
+mod sync_steal_cell;
+
+pub use atomic_steal_cell::*;
+#[cfg(not(feature = "no_std"))]
+pub use return_ticket::*;
+#[cfg(not(feature = "no_std"))]
+pub use sync_steal_cell::*;
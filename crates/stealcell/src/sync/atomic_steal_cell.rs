@@ -0,0 +1,383 @@
+use alloc::boxed::Box;
+#[cfg(not(feature = "no_std"))]
+use core::ops::{Deref, DerefMut};
+#[cfg(not(feature = "portable_atomic"))]
+use core::sync::atomic::AtomicPtr;
+use core::{
+	fmt::{self, Debug, Formatter},
+	marker::PhantomData,
+	num::NonZeroUsize,
+	panic::Location,
+	ptr,
+	sync::atomic::Ordering,
+};
+#[cfg(feature = "portable_atomic")]
+use portable_atomic::AtomicPtr;
+
+use crate::{
+	ShortTypeName, StealCell, StealError, StealViolation, Stolen, ViolationKind, handle_violation,
+	next_cell_id, value_type_name, violation::violation,
+};
+
+/// A lock-free cell that can be stolen from and returned to through a
+/// shared reference. The value is boxed, so that stealing is a single swap
+/// of a pointer, and returning a single compare-exchange.
+///
+/// Unlike [SyncStealCell](crate::sync::SyncStealCell), it never blocks,
+/// at the cost of an allocation for the value, which is made once when the
+/// cell is created, and moved in and out of the cell from then on.
+///
+/// Stealing acquires what the last return released, so whoever steals the
+/// value sees every change made to it by whoever returned it.
+///
+/// With the `portable_atomic` feature, it also works on targets without
+/// native atomic read-modify-write operations.
+pub struct AtomicStealCell<T> {
+	/// Null while stolen, otherwise made with `Box::into_raw`.
+	value: AtomicPtr<T>,
+	id: NonZeroUsize,
+	/// Included in panic and error messages, see
+	/// [AtomicStealCell::new_named].
+	label: Option<&'static str>,
+	/// Owns a `T`, but isn't `Send` or `Sync` on its own.
+	owns: PhantomData<*mut T>,
+}
+
+// SAFETY: The value is only ever moved between threads whole, never shared,
+// just like with a mutex.
+unsafe impl<T: Send> Send for AtomicStealCell<T> {}
+// SAFETY: Same as above, there is no access to the value through `&self`,
+// it can only be stolen.
+unsafe impl<T: Send> Sync for AtomicStealCell<T> {}
+
+/// A value stolen from an [AtomicStealCell], which can only be returned to
+/// it. Like [Stolen], it panics if dropped without returning it!
+#[must_use = "a stolen value must be returned with return_stolen or it will panic on drop"]
+pub struct AtomicStolen<T> {
+	stolen: Stolen<Box<T>>,
+	cell: NonZeroUsize,
+}
+
+impl<T> AtomicStealCell<T> {
+	/// Creates a cell with the value in it.
+	pub fn new(value: T) -> Self {
+		Self::with(Box::into_raw(Box::new(value)), None)
+	}
+
+	/// Creates a cell with a label, see [StealCell::new_named].
+	pub fn new_named(value: T, name: &'static str) -> Self {
+		Self::with(Box::into_raw(Box::new(value)), Some(name))
+	}
+
+	/// Creates a cell with nothing in it.
+	pub fn empty() -> Self {
+		Self::with(ptr::null_mut(), None)
+	}
+
+	fn with(value: *mut T, label: Option<&'static str>) -> Self {
+		Self {
+			value: AtomicPtr::new(value),
+			id: next_cell_id(),
+			label,
+			owns: PhantomData,
+		}
+	}
+
+	/// Returns the label of the cell, if it has one.
+	pub fn label(&self) -> Option<&'static str> {
+		self.label
+	}
+
+	/// Steals the value, see [StealCell::steal].
+	///
+	/// Panics if already stolen!
+	#[track_caller]
+	pub fn steal(&self) -> AtomicStolen<T> {
+		self.try_steal()
+			.unwrap_or_else(|error| StealCell::<T>::fail_steal(error))
+	}
+
+	/// Steals the value with a single swap, or returns an error if it's
+	/// already stolen. Only the stolen value remembers where it was stolen,
+	/// the cell doesn't, so the error can't tell.
+	#[must_use = "the stolen value must be returned with return_stolen or it will panic on drop"]
+	#[track_caller]
+	pub fn try_steal(&self) -> Result<AtomicStolen<T>, StealError> {
+		let value = self.value.swap(ptr::null_mut(), Ordering::Acquire);
+		if value.is_null() {
+			return Err(StealError::AlreadyStolen {
+				type_name: value_type_name::<T>(),
+				stolen_at: None,
+				label: self.label,
+			});
+		}
+		// SAFETY: Pointers in the cell are made with `Box::into_raw`, and the
+		// swap left this the only one owning it.
+		let value = unsafe { Box::from_raw(value) };
+		Ok(AtomicStolen {
+			stolen: Stolen::detached(value, self.label, Some(Location::caller())),
+			cell: self.id,
+		})
+	}
+
+	/// Returns the stolen value.
+	///
+	/// Panics if the value was stolen from a different cell, or if the cell
+	/// is not empty!
+	pub fn return_stolen(&self, stolen: AtomicStolen<T>) {
+		let Err(stolen) = self.try_return_stolen(stolen) else {
+			return;
+		};
+		let kind = if stolen.cell == self.id {
+			ViolationKind::CellOccupied
+		} else {
+			ViolationKind::WrongCell
+		};
+		let violation = StealViolation::new(
+			kind,
+			value_type_name::<T>(),
+			self.label,
+			stolen.stolen.stolen_at(),
+		);
+		stolen.stolen.discard();
+		if !handle_violation(|| violation) {
+			violation!(
+				violation,
+				"can't return the stolen value, {}! {}",
+				kind,
+				ShortTypeName(value_type_name::<T>())
+			);
+		}
+	}
+
+	/// Returns the stolen value with a single compare-exchange, or hands it
+	/// back if it was stolen from a different cell, or if the cell is not
+	/// empty.
+	pub fn try_return_stolen(&self, stolen: AtomicStolen<T>) -> Result<(), AtomicStolen<T>> {
+		if stolen.cell != self.id {
+			return Err(stolen);
+		}
+		let label = stolen.stolen.label();
+		let stolen_at = stolen.stolen.stolen_at();
+		let value = Box::into_raw(stolen.stolen.into_inner());
+		match self.value.compare_exchange(
+			ptr::null_mut(),
+			value,
+			Ordering::Release,
+			Ordering::Relaxed,
+		) {
+			Ok(_) => Ok(()),
+			Err(_) => Err(AtomicStolen {
+				// SAFETY: Made with `Box::into_raw` just above, and the
+				// compare-exchange didn't put it in the cell.
+				stolen: Stolen::detached(unsafe { Box::from_raw(value) }, label, stolen_at),
+				cell: self.id,
+			}),
+		}
+	}
+
+	/// Puts a value into the empty cell with a single compare-exchange, or
+	/// hands it back if the cell is not empty. Values stolen before can't be
+	/// returned while it's in the cell.
+	pub fn try_insert(&self, value: T) -> Result<(), T> {
+		let value = Box::into_raw(Box::new(value));
+		self.value
+			.compare_exchange(ptr::null_mut(), value, Ordering::Release, Ordering::Relaxed)
+			.map(drop)
+			// SAFETY: Made with `Box::into_raw` just above, and the
+			// compare-exchange didn't put it in the cell.
+			.map_err(|_| *unsafe { Box::from_raw(value) })
+	}
+
+	/// Returns `true` if the value is stolen, which may no longer be true by
+	/// the time it's used.
+	pub fn is_stolen(&self) -> bool {
+		self.value.load(Ordering::Relaxed).is_null()
+	}
+
+	/// Hands over the value, or `None` if it's stolen.
+	pub fn into_inner(mut self) -> Option<T> {
+		let value = core::mem::replace(self.value.get_mut(), ptr::null_mut());
+		// SAFETY: Pointers in the cell are made with `Box::into_raw`, and
+		// it's no longer in the cell.
+		(!value.is_null()).then(|| *unsafe { Box::from_raw(value) })
+	}
+}
+
+impl<T> Drop for AtomicStealCell<T> {
+	fn drop(&mut self) {
+		let value = *self.value.get_mut();
+		if !value.is_null() {
+			// SAFETY: Pointers in the cell are made with `Box::into_raw`, and
+			// the cell is going away.
+			drop(unsafe { Box::from_raw(value) });
+		}
+	}
+}
+
+impl<T> Default for AtomicStealCell<T>
+where
+	T: Default,
+{
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+impl<T> AtomicStolen<T> {
+	/// Returns a reference to the stolen value, see [Stolen::get].
+	pub fn get(&self) -> &T {
+		self.stolen.get()
+	}
+
+	/// Returns a mutable reference to the stolen value, see
+	/// [Stolen::get_mut].
+	pub fn get_mut(&mut self) -> &mut T {
+		self.stolen.get_mut()
+	}
+
+	/// Keeps the stolen value for good, see [Stolen::into_inner].
+	pub fn into_inner(self) -> T {
+		*self.stolen.into_inner()
+	}
+
+	/// Drops the stolen value without returning it, see [Stolen::discard].
+	pub fn discard(self) {
+		self.stolen.discard();
+	}
+}
+
+/// Prints the stolen value, like a plain [Stolen] value does.
+impl<T> Debug for AtomicStolen<T>
+where
+	T: Debug,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("AtomicStolen").field(self.get()).finish()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> Deref for AtomicStolen<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		self.get()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> DerefMut for AtomicStolen<T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.get_mut()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::{
+		sync::{
+			Arc,
+			atomic::{AtomicBool, AtomicUsize},
+		},
+		thread,
+	};
+
+	use super::*;
+
+	#[test]
+	fn steals_and_returns() {
+		let cell = AtomicStealCell::new_named(vec![1], "samples");
+		let mut stolen = cell.steal();
+		assert!(cell.is_stolen());
+		assert!(matches!(
+			cell.try_steal(),
+			Err(StealError::AlreadyStolen {
+				label: Some("samples"),
+				..
+			})
+		));
+		stolen.get_mut().push(2);
+		assert_eq!(format!("{stolen:?}"), "AtomicStolen([1, 2])");
+		cell.return_stolen(stolen);
+		assert!(!cell.is_stolen());
+		assert_eq!(cell.into_inner(), Some(vec![1, 2]));
+		assert_eq!(AtomicStealCell::<usize>::empty().into_inner(), None);
+	}
+
+	#[test]
+	fn hands_back_values_that_can_not_be_returned() {
+		let a = AtomicStealCell::new(1);
+		let b = AtomicStealCell::new(2);
+		let stolen = a.steal();
+		let stolen = b.try_return_stolen(stolen).unwrap_err();
+		assert_eq!(*stolen.get(), 1);
+		a.return_stolen(stolen);
+
+		let stolen = a.steal();
+		assert_eq!(a.try_insert(3), Ok(()));
+		assert_eq!(a.try_insert(4), Err(4));
+		let stolen = a.try_return_stolen(stolen).unwrap_err();
+		assert_eq!(*stolen.get(), 1);
+		stolen.discard();
+		assert_eq!(a.into_inner(), Some(3));
+	}
+
+	#[test]
+	#[should_panic(expected = "stolen value returned to an occupied cell")]
+	#[cfg(not(feature = "typed_panics"))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_when_returned_to_an_occupied_cell() {
+		let cell = AtomicStealCell::new(1);
+		let stolen = cell.steal();
+		cell.try_insert(2).unwrap();
+		cell.return_stolen(stolen);
+	}
+
+	#[test]
+	#[should_panic(expected = "stolen value returned to a different cell")]
+	#[cfg(not(feature = "typed_panics"))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	fn panics_when_returned_to_another_cell() {
+		let a = AtomicStealCell::new(1);
+		let b = AtomicStealCell::new(2);
+		b.steal().discard();
+		b.return_stolen(a.steal());
+	}
+
+	#[test]
+	fn only_one_thread_has_the_value_at_a_time() {
+		const THREADS: usize = 8;
+		const ATTEMPTS: usize = 2_000;
+		let cell = Arc::new(AtomicStealCell::new(0_usize));
+		let holding = Arc::new(AtomicBool::new(false));
+		let steals = Arc::new(AtomicUsize::new(0));
+
+		let handles: Vec<_> = (0..THREADS)
+			.map(|_| {
+				let (cell, holding, steals) =
+					(Arc::clone(&cell), Arc::clone(&holding), Arc::clone(&steals));
+				thread::spawn(move || {
+					for _ in 0..ATTEMPTS {
+						let Ok(mut stolen) = cell.try_steal() else {
+							continue;
+						};
+						assert!(!holding.swap(true, Ordering::SeqCst));
+						*stolen.get_mut() += 1;
+						steals.fetch_add(1, Ordering::Relaxed);
+						holding.store(false, Ordering::SeqCst);
+						cell.return_stolen(stolen);
+					}
+				})
+			})
+			.collect();
+		for handle in handles {
+			handle.join().unwrap();
+		}
+
+		let steals = steals.load(Ordering::Relaxed);
+		assert!(steals > 0);
+		let cell = Arc::into_inner(cell).unwrap();
+		assert_eq!(cell.into_inner(), Some(steals));
+	}
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use crate::{ReturnError, Stolen, sync::SyncStealCell};
+
+/// Returns a value stolen from a shared [SyncStealCell] to it from anywhere,
+/// like from a worker thread the value was sent to, without a reference to
+/// the cell, see [SyncStealCell::steal_with_ticket].
+///
+/// Dropping a ticket without redeeming it is harmless, the stolen value is
+/// still the one that has to be returned.
+#[must_use = "the ticket is needed to return the stolen value without the cell"]
+pub struct ReturnTicket<T> {
+	cell: Arc<SyncStealCell<T>>,
+}
+
+impl<T> SyncStealCell<T> {
+	/// Steals the value along with a ticket to return it with, which keeps
+	/// the cell alive, see [ReturnTicket]. The value can be returned on any
+	/// thread, even with the `debug_thread_affinity` feature.
+	///
+	/// Panics if already stolen, reporting where it was stolen first!
+	#[track_caller]
+	pub fn steal_with_ticket(self: &Arc<Self>) -> (Stolen<T>, ReturnTicket<T>) {
+		let ticket = ReturnTicket {
+			cell: Arc::clone(self),
+		};
+		#[cfg_attr(not(feature = "debug_thread_affinity"), expect(unused_mut))]
+		let mut stolen = self.steal();
+		#[cfg(feature = "debug_thread_affinity")]
+		stolen.release_thread();
+		(stolen, ticket)
+	}
+}
+
+impl<T> ReturnTicket<T> {
+	/// Returns the stolen value to the cell it was stolen from, see
+	/// [SyncStealCell::return_stolen].
+	///
+	/// Panics in the same cases as [SyncStealCell::return_stolen]!
+	pub fn redeem(self, stolen: Stolen<T>) {
+		self.cell.return_stolen(stolen);
+	}
+
+	/// Like [ReturnTicket::redeem] but instead of panicking, hands the
+	/// value back in the error if it can't be returned.
+	pub fn try_redeem(self, stolen: Stolen<T>) -> Result<(), ReturnError<T>> {
+		self.cell.try_return_stolen(stolen)
+	}
+
+	/// The cell the ticket returns to.
+	pub fn cell(&self) -> &Arc<SyncStealCell<T>> {
+		&self.cell
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::{sync::Arc, thread};
+
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	use crate::ReturnError;
+	use crate::sync::SyncStealCell;
+
+	#[test]
+	fn returns_from_another_thread() {
+		let buffer = Arc::new(SyncStealCell::new_named(vec![1], "buffer"));
+		let (mut stolen, ticket) = buffer.steal_with_ticket();
+		assert!(buffer.is_stolen());
+		thread::spawn(move || {
+			stolen.get_mut().push(2);
+			ticket.redeem(stolen);
+		})
+		.join()
+		.unwrap();
+		assert!(!buffer.is_stolen());
+		assert_eq!(
+			buffer.with_cell(|cell| cell.get().cloned()),
+			Some(vec![1, 2])
+		);
+	}
+
+	#[test]
+	fn unredeemed_tickets_are_harmless() {
+		let buffer = Arc::new(SyncStealCell::new(1));
+		let (stolen, ticket) = buffer.steal_with_ticket();
+		assert_eq!(Arc::strong_count(ticket.cell()), 2);
+		drop(ticket);
+		assert_eq!(Arc::strong_count(&buffer), 1);
+		buffer.return_stolen(stolen);
+		assert!(!buffer.is_stolen());
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn hands_back_values_of_other_cells() {
+		let buffer = Arc::new(SyncStealCell::new(1));
+		let other = SyncStealCell::new(2);
+		let (stolen, ticket) = buffer.steal_with_ticket();
+		let Err(ReturnError::WrongCell(other_stolen)) = ticket.try_redeem(other.steal()) else {
+			panic!("expected the value to belong to a different cell");
+		};
+		other.return_stolen(other_stolen);
+		buffer.return_stolen(stolen);
+	}
+
+	#[test]
+	fn tickets_can_be_sent_anywhere() {
+		fn assert_send_static<T: Send + 'static>() {}
+		assert_send_static::<super::ReturnTicket<Vec<usize>>>();
+	}
+}
@@ -0,0 +1,553 @@
+use core::{
+	fmt::{self, Display, Formatter},
+	time::Duration,
+};
+use std::{
+	collections::VecDeque,
+	sync::{Condvar, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{ReturnError, StealCell, StealError, Stolen, SwapError, violation::misuse};
+
+/// A [StealCell] behind a mutex, so that it can be stolen from and returned
+/// to through a shared reference, like from behind an `Arc`. The stolen
+/// values are the same [Stolen] values, which still panic if lost, on
+/// whichever thread they end up on.
+///
+/// The lock is only held while stealing or returning, not while the value
+/// is out.
+pub struct SyncStealCell<T> {
+	shared: Mutex<Shared<T>>,
+	/// Notified when the cell gets a value, see [SyncStealCell::steal_wait].
+	filled: Condvar,
+}
+
+struct Shared<T> {
+	cell: StealCell<T>,
+	/// The threads waiting for the value in a fair cell, see
+	/// [SyncStealCell::new_fair].
+	queue: Option<Queue>,
+}
+
+/// Tickets of the threads waiting for the value, longest waiting first.
+#[derive(Default)]
+struct Queue {
+	next: usize,
+	waiting: VecDeque<usize>,
+}
+
+impl<T> SyncStealCell<T> {
+	/// Creates a cell with the value in it.
+	pub fn new(value: T) -> Self {
+		Self::from(StealCell::new(value))
+	}
+
+	/// Creates a cell with a label, see [StealCell::new_named].
+	pub fn new_named(value: T, name: &'static str) -> Self {
+		Self::from(StealCell::new_named(value, name))
+	}
+
+	/// Creates a cell with nothing in it.
+	pub fn empty() -> Self {
+		Self::from(StealCell::empty())
+	}
+
+	/// Creates a cell with the value in it that hands the value to the
+	/// threads waiting with [SyncStealCell::steal_wait] in the order they
+	/// started waiting, so that a thread stealing it again right after
+	/// returning it can't starve the others.
+	///
+	/// This costs throughput, as every return wakes all the waiting threads
+	/// to find the next in line, and the value stays in the cell until that
+	/// thread gets to it, even if another one could take it sooner. Stealing
+	/// without waiting doesn't wait in line either.
+	pub fn new_fair(value: T) -> Self {
+		let cell = Self::new(value);
+		cell.lock().queue = Some(Queue::default());
+		cell
+	}
+
+	/// A panic while the lock is held can't leave the cell in a broken
+	/// state, so the lock is taken even if poisoned.
+	fn lock(&self) -> MutexGuard<'_, Shared<T>> {
+		self.shared.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+
+	/// Wakes the threads waiting for the value that is back in the cell.
+	fn notify_filled(&self, shared: &Shared<T>) {
+		if shared.queue.is_some() {
+			self.filled.notify_all();
+		} else {
+			self.filled.notify_one();
+		}
+	}
+
+	/// Steals the value, see [StealCell::steal].
+	///
+	/// Panics if already stolen, reporting where it was stolen first!
+	#[track_caller]
+	pub fn steal(&self) -> Stolen<T> {
+		let stolen = self.lock().cell.try_steal();
+		stolen.unwrap_or_else(|error| StealCell::<T>::fail_steal(error))
+	}
+
+	/// Steals the value once there is one in the cell, blocking the thread
+	/// until then. Waits forever if the value is lost.
+	#[must_use = "the stolen value must be returned with return_stolen or it will panic on drop"]
+	#[track_caller]
+	pub fn steal_wait(&self) -> Stolen<T> {
+		let mut shared = self.lock();
+		let ticket = shared.queue.as_mut().map(Queue::ticket);
+		let mut shared = self
+			.filled
+			.wait_while(shared, |shared| !shared.can_steal(ticket))
+			.unwrap_or_else(PoisonError::into_inner);
+		shared.leave(ticket);
+		shared.cell.steal()
+	}
+
+	/// Steals the value once there is one in the cell, like
+	/// [SyncStealCell::steal_wait], but gives up once `timeout` has passed,
+	/// leaving the cell as it was.
+	#[must_use = "the stolen value must be returned with return_stolen or it will panic on drop"]
+	#[track_caller]
+	pub fn steal_wait_timeout(&self, timeout: Duration) -> Result<Stolen<T>, WaitTimedOut> {
+		let mut shared = self.lock();
+		let ticket = shared.queue.as_mut().map(Queue::ticket);
+		let (mut shared, _) = self
+			.filled
+			.wait_timeout_while(shared, timeout, |shared| !shared.can_steal(ticket))
+			.unwrap_or_else(PoisonError::into_inner);
+		let in_line = shared.can_steal(ticket);
+		shared.leave(ticket);
+		if !in_line && !shared.cell.is_stolen() {
+			// Giving up the turn, the value goes to the next in line.
+			self.notify_filled(&shared);
+			return Err(WaitTimedOut {
+				timeout,
+				error: None,
+			});
+		}
+		shared.cell.try_steal().map_err(|error| WaitTimedOut {
+			timeout,
+			error: Some(error),
+		})
+	}
+
+	/// Steals the value, see [StealCell::try_steal].
+	#[must_use = "the stolen value must be returned with return_stolen or it will panic on drop"]
+	#[track_caller]
+	pub fn try_steal(&self) -> Result<Stolen<T>, StealError> {
+		self.lock().cell.try_steal()
+	}
+
+	/// Returns the stolen value, see [StealCell::return_stolen].
+	///
+	/// Panics in the same cases as [StealCell::return_stolen]!
+	pub fn return_stolen(&self, stolen: Stolen<T>) {
+		let result = self.try_return_stolen(stolen);
+		if let Err(error) = result {
+			StealCell::<T>::fail_return(error);
+		}
+	}
+
+	/// Returns the stolen value, see [StealCell::try_return_stolen].
+	pub fn try_return_stolen(&self, stolen: Stolen<T>) -> Result<(), ReturnError<T>> {
+		let mut shared = self.lock();
+		shared.cell.try_return_stolen(stolen)?;
+		self.notify_filled(&shared);
+		Ok(())
+	}
+
+	/// Swaps the values of the two cells, see [StealCell::swap]. The locks
+	/// are taken in the order the cells are in memory, so exchanging between
+	/// the same cells the other way around on another thread can't deadlock.
+	///
+	/// Panics if either cell is empty!
+	#[track_caller]
+	pub fn exchange(&self, other: &SyncStealCell<T>) {
+		if let Err(error) = self.try_exchange(other) {
+			misuse!(error.violation(), "{}", error);
+		}
+	}
+
+	/// Like [SyncStealCell::exchange] but instead of panicking, returns an
+	/// error saying which cells are empty, and why.
+	pub fn try_exchange(&self, other: &SyncStealCell<T>) -> Result<(), SwapError> {
+		if core::ptr::eq(self, other) {
+			let shared = self.lock();
+			return match shared.cell.is_stolen() {
+				false => Ok(()),
+				true => Err(SwapError::BothStolen(
+					shared.cell.already_stolen(),
+					shared.cell.already_stolen(),
+				)),
+			};
+		}
+		let (mut this, mut other) = if core::ptr::from_ref(self) < core::ptr::from_ref(other) {
+			let this = self.lock();
+			(this, other.lock())
+		} else {
+			let other = other.lock();
+			(self.lock(), other)
+		};
+		this.cell.try_swap(&mut other.cell)
+	}
+
+	/// Returns `true` if the value is stolen.
+	pub fn is_stolen(&self) -> bool {
+		self.lock().cell.is_stolen()
+	}
+
+	/// Calls `f` with the cell while holding the lock, for the rest of its
+	/// API. Wakes a thread waiting for the value if `f` leaves one in the
+	/// cell.
+	pub fn with_cell<R>(&self, f: impl FnOnce(&mut StealCell<T>) -> R) -> R {
+		let mut shared = self.lock();
+		let result = f(&mut shared.cell);
+		if !shared.cell.is_stolen() {
+			self.notify_filled(&shared);
+		}
+		result
+	}
+
+	/// Hands over the cell.
+	pub fn into_cell(self) -> StealCell<T> {
+		self.shared
+			.into_inner()
+			.unwrap_or_else(PoisonError::into_inner)
+			.cell
+	}
+}
+
+impl<T> Shared<T> {
+	/// Returns `true` if the thread holding the ticket can take the value,
+	/// once it's in the cell and the thread is the next in line, if waiting
+	/// in one.
+	fn can_steal(&self, ticket: Option<usize>) -> bool {
+		let next_in_line = match (&self.queue, ticket) {
+			(Some(queue), Some(ticket)) => queue.waiting.front() == Some(&ticket),
+			_ => true,
+		};
+		next_in_line && !self.cell.is_stolen()
+	}
+
+	/// Stops waiting in line.
+	fn leave(&mut self, ticket: Option<usize>) {
+		if let (Some(queue), Some(ticket)) = (&mut self.queue, ticket) {
+			queue.waiting.retain(|waiting| *waiting != ticket);
+		}
+	}
+}
+
+impl Queue {
+	/// Gets in line.
+	fn ticket(&mut self) -> usize {
+		let ticket = self.next;
+		self.next = self.next.wrapping_add(1);
+		self.waiting.push_back(ticket);
+		ticket
+	}
+}
+
+impl<T> From<StealCell<T>> for SyncStealCell<T> {
+	fn from(cell: StealCell<T>) -> Self {
+		Self {
+			shared: Mutex::new(Shared { cell, queue: None }),
+			filled: Condvar::new(),
+		}
+	}
+}
+
+impl<T> Default for SyncStealCell<T>
+where
+	T: Default,
+{
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+/// Returned by [SyncStealCell::steal_wait_timeout] when the value wasn't
+/// back in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitTimedOut {
+	/// How long the value was waited for.
+	pub timeout: Duration,
+	/// Why the value couldn't be stolen when giving up, `None` if it was in
+	/// the cell for a thread ahead in line, see [SyncStealCell::new_fair].
+	pub error: Option<StealError>,
+}
+
+impl Display for WaitTimedOut {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match &self.error {
+			Some(error) => write!(f, "timed out after {:?}, {error}", self.timeout),
+			None => write!(f, "timed out after {:?} waiting in line", self.timeout),
+		}
+	}
+}
+
+impl std::error::Error for WaitTimedOut {}
+
+#[cfg(test)]
+mod test {
+	use std::{sync::Arc, thread, time::Duration};
+
+	use super::*;
+
+	#[test]
+	fn steals_through_a_shared_reference() {
+		let cell = SyncStealCell::new(1);
+		let mut stolen = cell.steal();
+		assert!(cell.is_stolen());
+		assert!(matches!(
+			cell.try_steal(),
+			Err(StealError::AlreadyStolen { .. })
+		));
+		*stolen.get_mut() += 1;
+		cell.return_stolen(stolen);
+		assert!(!cell.is_stolen());
+		assert_eq!(cell.with_cell(|cell| cell.get().copied()), Some(2));
+		assert_eq!(cell.into_cell().into_inner(), Some(2));
+	}
+
+	/// Values can only be returned on the thread they were stolen on with
+	/// `debug_thread_affinity`.
+	#[test]
+	#[cfg(not(feature = "debug_thread_affinity"))]
+	fn returns_on_another_thread() {
+		let world = Arc::new(SyncStealCell::new_named(vec![1], "world"));
+		let stolen = thread::spawn({
+			let world = Arc::clone(&world);
+			move || {
+				let mut stolen = world.steal();
+				stolen.get_mut().push(2);
+				stolen
+			}
+		})
+		.join()
+		.unwrap();
+		assert!(world.is_stolen());
+
+		thread::spawn({
+			let world = Arc::clone(&world);
+			move || world.return_stolen(stolen)
+		})
+		.join()
+		.unwrap();
+		assert_eq!(
+			world.with_cell(|cell| cell.get().cloned()),
+			Some(vec![1, 2])
+		);
+	}
+
+	/// Values can only be returned on the thread they were stolen on with
+	/// `debug_thread_affinity`.
+	#[test]
+	#[cfg(not(feature = "debug_thread_affinity"))]
+	fn waiters_take_turns() {
+		const WAITERS: usize = 4;
+		let turns = Arc::new(SyncStealCell::new(Vec::new()));
+		let held = turns.steal();
+
+		let waiters: Vec<_> = (0..WAITERS)
+			.map(|waiter| {
+				let turns = Arc::clone(&turns);
+				thread::spawn(move || {
+					let mut stolen = turns.steal_wait();
+					stolen.get_mut().push(waiter);
+					thread::sleep(Duration::from_millis(1));
+					turns.return_stolen(stolen);
+				})
+			})
+			.collect();
+		thread::sleep(Duration::from_millis(10));
+		assert!(turns.is_stolen());
+		turns.return_stolen(held);
+		for waiter in waiters {
+			waiter.join().unwrap();
+		}
+
+		let mut turns = Arc::into_inner(turns)
+			.unwrap()
+			.into_cell()
+			.into_inner()
+			.unwrap();
+		turns.sort_unstable();
+		assert_eq!(turns, [0, 1, 2, 3]);
+	}
+
+	#[test]
+	fn fair_waiters_take_turns() {
+		const WAITERS: usize = 4;
+		const TURNS: usize = 10;
+		let turns = Arc::new(SyncStealCell::new_fair(Vec::new()));
+		let held = turns.steal();
+
+		let waiting =
+			|turns: &SyncStealCell<Vec<usize>>| turns.lock().queue.as_ref().unwrap().waiting.len();
+
+		// Each waiter gets in line before the next one starts.
+		let waiters: Vec<_> = (0..WAITERS)
+			.map(|waiter| {
+				let handle = thread::spawn({
+					let turns = Arc::clone(&turns);
+					move || {
+						for _ in 0..TURNS {
+							let mut stolen = turns.steal_wait();
+							stolen.get_mut().push(waiter);
+							turns.return_stolen(stolen);
+						}
+					}
+				});
+				while waiting(&turns) <= waiter {
+					thread::yield_now();
+				}
+				handle
+			})
+			.collect();
+		turns.return_stolen(held);
+		for waiter in waiters {
+			waiter.join().unwrap();
+		}
+
+		let turns = Arc::into_inner(turns)
+			.unwrap()
+			.into_cell()
+			.into_inner()
+			.unwrap();
+		assert_eq!(turns.len(), WAITERS * TURNS);
+		assert_eq!(turns[..WAITERS], [0, 1, 2, 3]);
+		for waiter in 0..WAITERS {
+			let count = turns.iter().filter(|turn| **turn == waiter).count();
+			assert_eq!(count, TURNS);
+		}
+	}
+
+	#[test]
+	fn fair_waiters_giving_up_leave_the_line() {
+		let cell = SyncStealCell::new_fair(1);
+		let stolen = cell.steal();
+		assert!(cell.steal_wait_timeout(Duration::from_millis(1)).is_err());
+		assert!(cell.lock().queue.as_ref().unwrap().waiting.is_empty());
+		cell.return_stolen(stolen);
+		assert_eq!(cell.steal_wait().into_inner(), 1);
+	}
+
+	#[test]
+	#[cfg(not(feature = "slim_panics"))]
+	fn waiting_times_out() {
+		let cell = SyncStealCell::new_named(1, "score");
+		let stolen = cell.steal();
+		let started = std::time::Instant::now();
+		let error = cell
+			.steal_wait_timeout(Duration::from_millis(10))
+			.unwrap_err();
+		assert!(started.elapsed() >= Duration::from_millis(10));
+		assert!(started.elapsed() < Duration::from_secs(5));
+		assert_eq!(error.timeout, Duration::from_millis(10));
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		assert!(
+			error
+				.to_string()
+				.starts_with("timed out after 10ms, value already stolen from 'score': i32")
+		);
+		assert!(cell.is_stolen());
+		cell.return_stolen(stolen);
+		assert_eq!(
+			cell.steal_wait_timeout(Duration::ZERO)
+				.unwrap()
+				.into_inner(),
+			1
+		);
+	}
+
+	#[test]
+	fn exchanges_the_other_way_around_without_deadlocking() {
+		let front = Arc::new(SyncStealCell::new(1));
+		let back = Arc::new(SyncStealCell::new(2));
+		let exchanging: Vec<_> = [
+			(Arc::clone(&front), Arc::clone(&back)),
+			(Arc::clone(&back), Arc::clone(&front)),
+		]
+		.into_iter()
+		.map(|(this, other)| {
+			thread::spawn(move || {
+				for _ in 0..1000 {
+					this.exchange(&other);
+				}
+			})
+		})
+		.collect();
+		for exchanging in exchanging {
+			exchanging.join().unwrap();
+		}
+
+		let mut values = [front.steal().into_inner(), back.steal().into_inner()];
+		values.sort_unstable();
+		assert_eq!(values, [1, 2]);
+	}
+
+	#[test]
+	fn exchanging_with_an_empty_cell_changes_neither() {
+		let front = SyncStealCell::new_named(1, "front");
+		let back = SyncStealCell::new_named(2, "back");
+		let stolen = back.steal();
+		let error = front.try_exchange(&back).unwrap_err();
+		assert!(matches!(error, SwapError::OtherStolen(_)));
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		assert!(
+			error
+				.to_string()
+				.starts_with("can't swap into an empty cell, value already stolen from 'back'")
+		);
+		assert!(matches!(
+			back.try_exchange(&front),
+			Err(SwapError::ThisStolen(_))
+		));
+		assert!(matches!(
+			back.try_exchange(&back),
+			Err(SwapError::BothStolen(..))
+		));
+		front.exchange(&front);
+		back.return_stolen(stolen);
+		assert_eq!(front.steal().into_inner(), 1);
+		assert_eq!(back.steal().into_inner(), 2);
+	}
+
+	#[test]
+	fn wakes_waiters_when_filled() {
+		let cell = Arc::new(SyncStealCell::<usize>::empty());
+		let waiter = thread::spawn({
+			let cell = Arc::clone(&cell);
+			move || cell.steal_wait().into_inner()
+		});
+		thread::sleep(Duration::from_millis(10));
+		cell.with_cell(|cell| cell.set(1).unwrap());
+		assert_eq!(waiter.join().unwrap(), 1);
+	}
+
+	#[test]
+	#[should_panic(expected = "value already stolen from 'world'")]
+	#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+	#[cfg(not(feature = "abort_on_loss"))]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn panics_when_already_stolen() {
+		let world = SyncStealCell::new_named(1, "world");
+		world.steal().discard();
+		let _ = world.steal();
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn hands_back_values_that_can_not_be_returned() {
+		let a = SyncStealCell::new(1);
+		let b = SyncStealCell::new(2);
+		let stolen = a.steal();
+		let Err(ReturnError::WrongCell(stolen)) = b.try_return_stolen(stolen) else {
+			panic!("expected the value to belong to a different cell");
+		};
+		a.return_stolen(stolen);
+	}
+}
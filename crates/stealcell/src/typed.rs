@@ -0,0 +1,189 @@
+//! A [StealCell](crate::StealCell) that tracks whether its value is stolen
+//! in its type, so that a missing return is a type error instead of a
+//! panic, where the pairing of steals and returns is known statically.
+
+#[cfg(not(feature = "no_std"))]
+use core::ops::{Deref, DerefMut};
+use core::{
+	fmt::{self, Debug, Formatter},
+	marker::PhantomData,
+};
+
+use crate::{ReturnError, Stolen};
+
+/// The state of a [StealCell] that has its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Present;
+
+/// The state of a [StealCell] whose value is stolen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Empty;
+
+/// A cell whose `State` tells whether its value is stolen, either [Present]
+/// or [Empty]. Stealing consumes a present cell and hands back an empty one,
+/// which only turns present again by returning the stolen value to it.
+///
+/// Stolen values are the same [Stolen] values the dynamic cell hands out,
+/// which are still checked to be returned to the cell they came from.
+pub struct StealCell<T, State = Present> {
+	cell: crate::StealCell<T>,
+	state: PhantomData<State>,
+}
+
+impl<T, State> StealCell<T, State> {
+	fn with_state<To>(self) -> StealCell<T, To> {
+		StealCell {
+			cell: self.cell,
+			state: PhantomData,
+		}
+	}
+
+	/// Returns the label of the cell, if it has one.
+	pub fn label(&self) -> Option<&'static str> {
+		self.cell.label()
+	}
+}
+
+impl<T> StealCell<T, Present> {
+	/// Creates a cell with the value in it.
+	pub fn new(value: T) -> Self {
+		Self {
+			cell: crate::StealCell::new(value),
+			state: PhantomData,
+		}
+	}
+
+	/// Creates a cell with a label, see
+	/// [StealCell::new_named](crate::StealCell::new_named).
+	pub fn new_named(value: T, name: &'static str) -> Self {
+		Self {
+			cell: crate::StealCell::new_named(value, name),
+			state: PhantomData,
+		}
+	}
+
+	/// Returns a reference to the value, which is always there.
+	///
+	/// PANIC SAFETY: Present cells can only be made with a value, or by
+	/// returning one.
+	pub fn get(&self) -> &T {
+		self.cell.get().unwrap()
+	}
+
+	/// Returns a mutable reference to the value, which is always there.
+	///
+	/// PANIC SAFETY: Present cells can only be made with a value, or by
+	/// returning one.
+	pub fn get_mut(&mut self) -> &mut T {
+		self.cell.get_mut().unwrap()
+	}
+
+	/// Steals the value, handing back the empty cell it must be returned to.
+	#[must_use = "the stolen value must be returned with return_stolen or it will panic on drop"]
+	#[track_caller]
+	pub fn steal(mut self) -> (Stolen<T>, StealCell<T, Empty>) {
+		let stolen = self.cell.steal();
+		(stolen, self.with_state())
+	}
+
+	/// Hands over the value, dropping the cell.
+	///
+	/// PANIC SAFETY: Present cells can only be made with a value, or by
+	/// returning one.
+	pub fn into_inner(self) -> T {
+		self.cell.into_inner().unwrap()
+	}
+}
+
+impl<T> StealCell<T, Empty> {
+	/// Returns the stolen value, handing back the cell with its value.
+	///
+	/// Panics if the value was stolen from a different cell, like
+	/// [StealCell::return_stolen](crate::StealCell::return_stolen) does!
+	pub fn return_stolen(mut self, stolen: Stolen<T>) -> StealCell<T, Present> {
+		self.cell.return_stolen(stolen);
+		self.with_state()
+	}
+
+	/// Like [StealCell::return_stolen] but instead of panicking, hands back
+	/// the cell, still empty, along with the error.
+	// Handed back as is, just like the stolen value in the error.
+	#[allow(clippy::result_large_err)]
+	pub fn try_return_stolen(
+		mut self,
+		stolen: Stolen<T>,
+	) -> Result<StealCell<T, Present>, (Self, ReturnError<T>)> {
+		match self.cell.try_return_stolen(stolen) {
+			Ok(()) => Ok(self.with_state()),
+			Err(error) => Err((self, error)),
+		}
+	}
+}
+
+impl<T, State> Debug for StealCell<T, State>
+where
+	T: Debug,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		self.cell.fmt(f)
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> Deref for StealCell<T, Present> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		self.get()
+	}
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T> DerefMut for StealCell<T, Present> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.get_mut()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	struct World {
+		physics: StealCell<usize>,
+		frame: usize,
+	}
+
+	#[test]
+	fn steals_and_returns() {
+		let mut world = World {
+			physics: StealCell::new_named(1, "physics"),
+			frame: 0,
+		};
+		world.frame += 1;
+		let (mut physics, empty) = world.physics.steal();
+		*physics.get_mut() += world.frame;
+		world = World {
+			physics: empty.return_stolen(physics),
+			frame: world.frame,
+		};
+		assert_eq!(*world.physics.get(), 2);
+		#[cfg(any(debug_assertions, feature = "diagnostics"))]
+		assert_eq!(world.physics.label(), Some("physics"));
+		*world.physics.get_mut() += 1;
+		assert_eq!(world.physics.into_inner(), 3);
+	}
+
+	#[test]
+	#[cfg(any(debug_assertions, feature = "diagnostics"))]
+	fn hands_back_the_cell_when_the_return_fails() {
+		let (stolen, empty) = StealCell::new(1).steal();
+		let (other, other_empty) = StealCell::new(2).steal();
+		let Err((empty, ReturnError::WrongCell(other))) = empty.try_return_stolen(other) else {
+			panic!("expected the value to belong to a different cell");
+		};
+		assert_eq!(format!("{empty:?}"), "StealCell(<stolen>)");
+		assert_eq!(*empty.return_stolen(stolen).get(), 1);
+		assert_eq!(*other_empty.return_stolen(other).get(), 2);
+	}
+}
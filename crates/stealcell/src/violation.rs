@@ -0,0 +1,340 @@
+use core::{
+	fmt::{self, Display, Formatter},
+	panic::Location,
+};
+
+#[cfg(any(feature = "slim_panics", feature = "typed_panics"))]
+use crate::SwapError;
+use crate::{ReturnError, ShortTypeName, StealError, Stolen, value_type_name};
+
+/// The kind of rule a [StealViolation] broke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViolationKind {
+	/// Tried to steal a value that was already stolen.
+	AlreadyStolen,
+	/// Tried to steal a value that was lost, see
+	/// [StealCell::is_poisoned](crate::StealCell::is_poisoned).
+	Poisoned,
+	/// A stolen value was dropped without being returned.
+	Lost,
+	/// Tried to return a stolen value to a cell that is not empty.
+	CellOccupied,
+	/// Tried to return a stolen value to a different cell.
+	WrongCell,
+	/// Tried to return a stolen value to a cell that was refilled since.
+	Stale,
+	/// Tried to return a stolen value that was already returned.
+	AlreadyReturned,
+	/// Tried to return a stolen value on a different thread than it was
+	/// stolen on, with the `debug_thread_affinity` feature.
+	WrongThread,
+}
+
+#[cfg(all(feature = "slim_panics", not(feature = "typed_panics")))]
+impl ViolationKind {
+	/// The fixed message to panic with when using the `slim_panics` feature.
+	pub(crate) fn slim_message(self) -> &'static str {
+		match self {
+			Self::AlreadyStolen => "stealcell: value already stolen",
+			Self::Poisoned => "stealcell: value was lost, cell is poisoned",
+			Self::Lost => "stealcell: stolen value lost without returning it",
+			Self::CellOccupied => "stealcell: stolen value returned to an occupied cell",
+			Self::WrongCell => "stealcell: stolen value returned to a different cell",
+			Self::Stale => "stealcell: stolen value returned to a refilled cell",
+			Self::AlreadyReturned => "stealcell: stolen value already returned",
+			Self::WrongThread => "stealcell: stolen value returned on a different thread",
+		}
+	}
+}
+
+impl Display for ViolationKind {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::AlreadyStolen => "value already stolen",
+			Self::Poisoned => "value was lost, cell is poisoned",
+			Self::Lost => "stolen value lost without returning it",
+			Self::CellOccupied => "stolen value returned to an occupied cell",
+			Self::WrongCell => "stolen value returned to a different cell",
+			Self::Stale => "stolen value returned to a refilled cell",
+			Self::AlreadyReturned => "stolen value already returned",
+			Self::WrongThread => "stolen value returned on a different thread",
+		})
+	}
+}
+
+/// Describes a broken promise, like losing a stolen value, handed to the
+/// handler installed with [set_violation_handler].
+///
+/// With the `typed_panics` feature, it's also the payload of every panic,
+/// to be downcast after [catch_unwind](std::panic::catch_unwind).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StealViolation {
+	/// What went wrong.
+	pub kind: ViolationKind,
+	/// The type name of the stolen value.
+	pub type_name: &'static str,
+	/// The label of the cell, see
+	/// [StealCell::new_named](crate::StealCell::new_named).
+	pub label: Option<&'static str>,
+	/// Where the value was stolen, if it's known.
+	pub stolen_at: Option<&'static Location<'static>>,
+}
+
+impl StealViolation {
+	pub(crate) fn new(
+		kind: ViolationKind,
+		type_name: &'static str,
+		label: Option<&'static str>,
+		stolen_at: Option<&'static Location<'static>>,
+	) -> Self {
+		Self {
+			kind,
+			type_name,
+			label,
+			stolen_at,
+		}
+	}
+}
+
+impl Display for StealViolation {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "{}: {}", self.kind, ShortTypeName(self.type_name))?;
+		match (self.label, self.stolen_at) {
+			(Some(label), Some(stolen_at)) => write!(f, ", stolen from '{label}' at {stolen_at}"),
+			(Some(label), None) => write!(f, ", stolen from '{label}'"),
+			(None, Some(stolen_at)) => write!(f, ", stolen at {stolen_at}"),
+			(None, None) => Ok(()),
+		}
+	}
+}
+
+impl StealError {
+	pub(crate) fn violation(&self) -> StealViolation {
+		match *self {
+			Self::AlreadyStolen {
+				type_name,
+				stolen_at,
+				label,
+			} => StealViolation::new(ViolationKind::AlreadyStolen, type_name, label, stolen_at),
+			Self::Poisoned {
+				type_name,
+				stolen_at,
+				label,
+			} => StealViolation::new(ViolationKind::Poisoned, type_name, label, stolen_at),
+		}
+	}
+}
+
+/// Only needed for slim and typed panics, which can only tell about the
+/// first empty cell, unlike the message.
+#[cfg(any(feature = "slim_panics", feature = "typed_panics"))]
+impl SwapError {
+	pub(crate) fn violation(&self) -> StealViolation {
+		match self {
+			Self::ThisStolen(error) | Self::OtherStolen(error) | Self::BothStolen(error, _) => {
+				error.violation()
+			}
+		}
+	}
+}
+
+impl<T> ReturnError<T> {
+	pub(crate) fn violation(&self) -> StealViolation {
+		let (kind, stolen) = match self {
+			Self::CellOccupied(stolen) => (ViolationKind::CellOccupied, Some(stolen)),
+			Self::WrongCell(stolen) => (ViolationKind::WrongCell, Some(stolen)),
+			Self::Stale(stolen) => (ViolationKind::Stale, Some(stolen)),
+			Self::AlreadyReturned => (ViolationKind::AlreadyReturned, None),
+		};
+		StealViolation::new(
+			kind,
+			value_type_name::<T>(),
+			stolen.and_then(Stolen::label),
+			stolen.and_then(Stolen::stolen_at),
+		)
+	}
+}
+
+#[cfg(feature = "violation_handler")]
+static VIOLATION_HANDLER: std::sync::RwLock<Option<fn(&StealViolation)>> =
+	std::sync::RwLock::new(None);
+
+/// Installs `handler` to be called on every violation, process wide,
+/// returning the previously installed one.
+///
+/// When the handler returns, a lost value is dropped silently, and a
+/// value that couldn't be returned is dropped too, leaving the cell as it
+/// was. Stealing an already stolen value still panics after calling the
+/// handler, as there is nothing to hand out. Without a handler, every
+/// violation panics, which is the default.
+///
+/// With the `abort_on_loss` feature, the handler still comes first, the
+/// process only aborts when there is no handler, or when stealing an already
+/// stolen value once the handler returns.
+#[cfg(feature = "violation_handler")]
+pub fn set_violation_handler(handler: fn(&StealViolation)) -> Option<fn(&StealViolation)> {
+	VIOLATION_HANDLER
+		.write()
+		.unwrap_or_else(std::sync::PoisonError::into_inner)
+		.replace(handler)
+}
+
+/// Removes the handler installed with [set_violation_handler], going back
+/// to panicking on every violation.
+#[cfg(feature = "violation_handler")]
+pub fn reset_violation_handler() -> Option<fn(&StealViolation)> {
+	VIOLATION_HANDLER
+		.write()
+		.unwrap_or_else(std::sync::PoisonError::into_inner)
+		.take()
+}
+
+/// Calls the installed handler with the violation, returning `false` if
+/// there is none, in which case the caller panics like it always did.
+#[cfg_attr(not(feature = "violation_handler"), expect(unused_variables))]
+pub(crate) fn handle_violation(violation: impl FnOnce() -> StealViolation) -> bool {
+	#[cfg(feature = "violation_handler")]
+	{
+		let handler = *VIOLATION_HANDLER
+			.read()
+			.unwrap_or_else(std::sync::PoisonError::into_inner);
+		if let Some(handler) = handler {
+			handler(&violation());
+			return true;
+		}
+	}
+	false
+}
+
+/// Panics with the formatted message, or aborts with the `abort_on_loss`
+/// feature, only used once the violation handler had its say.
+///
+/// With the `typed_panics` feature, it panics with the [StealViolation]
+/// itself instead. Otherwise with the `slim_panics` feature, it panics with
+/// the fixed message of its kind, and the rest is not formatted at all.
+macro_rules! violation {
+	($violation:expr, $message:literal $(, $argument:expr)* $(,)?) => {{
+		#[cfg(feature = "typed_panics")]
+		{
+			$(let _ = &$argument;)*
+			$crate::violation::violated_typed($violation)
+		}
+		#[cfg(all(feature = "slim_panics", not(feature = "typed_panics")))]
+		{
+			$(let _ = &$argument;)*
+			$crate::violation::violated_slim($violation.kind.slim_message())
+		}
+		#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+		$crate::violation::violated(format_args!($message $(, $argument)*))
+	}};
+}
+
+/// Like [violation] but always panics, for misuse that doesn't break the
+/// promise of returning stolen values, like reading from an empty cell.
+macro_rules! misuse {
+	($violation:expr, $message:literal $(, $argument:expr)* $(,)?) => {{
+		#[cfg(feature = "typed_panics")]
+		{
+			$(let _ = &$argument;)*
+			std::panic::panic_any($violation)
+		}
+		#[cfg(all(feature = "slim_panics", not(feature = "typed_panics")))]
+		{
+			$(let _ = &$argument;)*
+			$crate::violation::misused_slim($violation.kind.slim_message())
+		}
+		#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+		panic!($message $(, $argument)*)
+	}};
+}
+
+pub(crate) use {misuse, violation};
+
+/// Panics with `message`, or aborts with the `abort_on_loss` feature.
+#[cfg(not(any(feature = "slim_panics", feature = "typed_panics")))]
+#[track_caller]
+pub(crate) fn violated(message: fmt::Arguments<'_>) -> ! {
+	#[cfg(feature = "abort_on_loss")]
+	abort(&message);
+	#[cfg(not(feature = "abort_on_loss"))]
+	panic!("{message}");
+}
+
+/// Panicking can't unwind out of an `extern "C"` function, so the process
+/// aborts right after the panic handler reported the message. Unlike
+/// `std::process::abort`, this works without std too.
+#[cfg(all(
+	feature = "abort_on_loss",
+	not(any(feature = "slim_panics", feature = "typed_panics"))
+))]
+extern "C" fn abort(message: &fmt::Arguments<'_>) -> ! {
+	panic!("{message}");
+}
+
+/// Like [violated] but with a fixed message. It's not generic, so its
+/// formatting code is not repeated for every value type.
+#[cfg(all(feature = "slim_panics", not(feature = "typed_panics")))]
+#[cold]
+#[track_caller]
+pub(crate) fn violated_slim(message: &'static str) -> ! {
+	#[cfg(feature = "abort_on_loss")]
+	abort_slim(&message);
+	#[cfg(not(feature = "abort_on_loss"))]
+	panic!("{message}");
+}
+
+/// Like [abort] but with a fixed message.
+#[cfg(all(
+	feature = "abort_on_loss",
+	feature = "slim_panics",
+	not(feature = "typed_panics")
+))]
+extern "C" fn abort_slim(message: &&'static str) -> ! {
+	panic!("{message}");
+}
+
+/// Panics with a fixed message, see [misuse].
+#[cfg(all(feature = "slim_panics", not(feature = "typed_panics")))]
+#[cold]
+#[track_caller]
+pub(crate) fn misused_slim(message: &'static str) -> ! {
+	panic!("{message}");
+}
+
+/// Like [violated] but panics with the violation itself as the payload.
+#[cfg(feature = "typed_panics")]
+#[track_caller]
+pub(crate) fn violated_typed(violation: StealViolation) -> ! {
+	#[cfg(feature = "abort_on_loss")]
+	abort_typed(&violation);
+	#[cfg(not(feature = "abort_on_loss"))]
+	std::panic::panic_any(violation);
+}
+
+/// Like [abort] but with the violation as the payload.
+#[cfg(all(feature = "abort_on_loss", feature = "typed_panics"))]
+extern "C" fn abort_typed(violation: &StealViolation) -> ! {
+	std::panic::panic_any(*violation);
+}
+
+#[cfg(test)]
+mod test {
+	use core::panic::Location;
+
+	use crate::{StealViolation, ViolationKind};
+
+	#[test]
+	fn displays_the_kind_type_name_and_location() {
+		let location = Location::caller();
+		let violation = StealViolation::new(ViolationKind::Lost, "usize", None, Some(location));
+		assert_eq!(
+			violation.to_string(),
+			format!("stolen value lost without returning it: usize, stolen at {location}")
+		);
+		let violation = StealViolation::new(ViolationKind::AlreadyReturned, "usize", None, None);
+		assert_eq!(
+			violation.to_string(),
+			"stolen value already returned: usize"
+		);
+	}
+}
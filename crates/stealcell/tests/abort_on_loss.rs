@@ -0,0 +1,93 @@
+//! Aborting takes the whole process down, so every case re-runs this test
+//! binary, only running the violation in the child process.
+#![cfg(feature = "abort_on_loss")]
+
+use std::{env, process::Command};
+
+use stealcell::StealCell;
+
+const CASE: &str = "STEALCELL_ABORT_CASE";
+
+fn aborts(name: &str, violation: impl FnOnce()) -> String {
+	if env::var(CASE).is_ok_and(|case| case == name) {
+		violation();
+		unreachable!("the violation should have aborted");
+	}
+
+	let output = Command::new(env::current_exe().unwrap())
+		.args([name, "--exact", "--nocapture", "--test-threads=1"])
+		.env(CASE, name)
+		.output()
+		.unwrap();
+	assert!(!output.status.success());
+	#[cfg(unix)]
+	{
+		use std::os::unix::process::ExitStatusExt;
+		const SIGABRT: i32 = 6;
+		assert_eq!(output.status.signal(), Some(SIGABRT));
+	}
+	String::from_utf8(output.stderr).unwrap()
+}
+
+/// Checks the message of the violation, only formatted without the
+/// `slim_panics` and `typed_panics` features.
+fn assert_printed(stderr: &str, message: &str) {
+	if cfg!(not(any(feature = "slim_panics", feature = "typed_panics"))) {
+		assert!(stderr.contains(message), "{stderr}");
+	}
+}
+
+#[test]
+fn losing_a_stolen_value_aborts() {
+	let stderr = aborts("losing_a_stolen_value_aborts", || {
+		let mut stealcell = StealCell::<usize>::new(1);
+		drop(stealcell.steal());
+	});
+	assert_printed(
+		&stderr,
+		"You've lost a stolen value without returning it first! usize",
+	);
+}
+
+#[test]
+fn losing_a_stolen_value_while_panicking_aborts() {
+	let stderr = aborts("losing_a_stolen_value_while_panicking_aborts", || {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+			let _stolen = stealcell.steal();
+			panic!("the original panic");
+		}));
+	});
+	assert!(stderr.contains("the original panic"));
+	assert_printed(
+		&stderr,
+		"You've lost a stolen value without returning it first! usize",
+	);
+}
+
+#[test]
+fn stealing_twice_aborts() {
+	let stderr = aborts("stealing_twice_aborts", || {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let first = stealcell.steal();
+		let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+			let _ = stealcell.steal();
+		}));
+		stealcell.return_stolen(first);
+	});
+	assert_printed(&stderr, "value already stolen from:");
+}
+
+#[test]
+fn returning_to_an_occupied_cell_aborts() {
+	let stderr = aborts("returning_to_an_occupied_cell_aborts", || {
+		let mut stealcell = StealCell::<usize>::new(1);
+		let stolen = stealcell.steal();
+		stealcell.set(2).unwrap();
+		stealcell.return_stolen(stolen);
+	});
+	assert_printed(
+		&stderr,
+		"trying to return a stolen value, but this cell is not empty!",
+	);
+}
@@ -0,0 +1,221 @@
+//! The macros live in a separate crate, so they can only be tested from
+//! outside.
+#![cfg(feature = "derive")]
+
+use stealcell::{StealCell, StealPlaceholder, Stealable, with_stolen};
+
+#[derive(Debug, Default, PartialEq)]
+struct Physics {
+	steps: usize,
+}
+
+#[derive(Stealable)]
+struct World<T: Clone, const N: usize> {
+	physics: StealCell<Physics>,
+	inventory: StealCell<StealCell<T>>,
+	items: [T; N],
+	frame: usize,
+}
+
+impl<T: Clone, const N: usize> World<T, N> {
+	fn new(item: T) -> Self {
+		Self {
+			physics: StealCell::new(Physics::default()),
+			inventory: StealCell::new(StealCell::new(item.clone())),
+			items: core::array::from_fn(|_| item.clone()),
+			frame: 0,
+		}
+	}
+}
+
+#[test]
+fn views_every_other_field() {
+	let mut world = World::<&str, 2>::new("sword");
+	let (mut physics, view) = world.steal_physics();
+	let view: WorldWithoutPhysics<'_, &str, 2> = view;
+	*view.frame += 1;
+	view.items[1] = "shield";
+	physics.get_mut().steps += *view.frame;
+	assert!(!view.inventory.is_stolen());
+	World::return_physics(physics, view);
+
+	assert_eq!(world.physics, Physics { steps: 1 });
+	assert_eq!(world.items, ["sword", "shield"]);
+	assert_eq!(world.frame, 1);
+}
+
+#[test]
+fn steals_cells_of_cells() {
+	let mut world = World::<usize, 1>::new(1);
+	let (mut inventory, view) = world.steal_inventory();
+	let item = inventory.get_mut().steal();
+	view.items[0] += item.get();
+	inventory.get_mut().return_stolen(item);
+	assert!(!view.physics.is_stolen());
+	World::return_inventory(inventory, view);
+
+	assert_eq!(world.items, [2]);
+	assert_eq!(world.inventory.as_ref(), &StealCell::new(1));
+}
+
+#[test]
+#[should_panic(expected = "already stolen")]
+#[cfg(not(feature = "abort_on_loss"))]
+fn stealing_twice_panics() {
+	let mut world = World::<usize, 0>::new(1);
+	let (_stolen, _) = world.steal_physics();
+	let _again = world.steal_physics();
+}
+
+#[cfg(not(feature = "no_std"))]
+#[derive(Debug, PartialEq, StealPlaceholder)]
+struct Inventory<T> {
+	items: Vec<T>,
+	equipped: Option<T>,
+	owner: String,
+}
+
+#[cfg(not(feature = "no_std"))]
+#[derive(Debug, PartialEq, StealPlaceholder)]
+struct Slot(Option<usize>, Inventory<usize>);
+
+#[derive(Debug, PartialEq, StealPlaceholder)]
+struct Nothing;
+
+#[test]
+fn derives_placeholders_for_unit_structs() {
+	assert_eq!(Nothing::placeholder(), Nothing);
+}
+
+#[test]
+#[cfg(not(feature = "no_std"))]
+fn derives_placeholders_from_the_fields() {
+	assert_eq!(
+		Slot::placeholder(),
+		Slot(
+			None,
+			Inventory {
+				items: Vec::new(),
+				equipped: None,
+				owner: String::new(),
+			}
+		)
+	);
+
+	let mut stealcell = StealCell::new(Inventory {
+		items: vec!["sword"],
+		equipped: Some("shield"),
+		owner: "hero".to_string(),
+	});
+	let stolen = stealcell.steal_replace_placeholder();
+	assert!(stealcell.as_ref().items.is_empty());
+	stealcell.return_stolen(stolen);
+	assert_eq!(stealcell.as_ref().owner, "hero");
+}
+
+struct Game {
+	physics: StealCell<Physics>,
+	score: StealCell<usize>,
+	frame: usize,
+}
+
+impl Game {
+	fn new() -> Self {
+		Self {
+			physics: StealCell::new(Physics::default()),
+			score: StealCell::new(0),
+			frame: 0,
+		}
+	}
+
+	#[with_stolen(self.physics)]
+	fn step(&mut self, by: usize, physics: &mut Physics) -> usize {
+		assert!(self.physics.is_stolen());
+		self.frame += by;
+		physics.steps += self.frame;
+		physics.steps
+	}
+
+	#[with_stolen(self.physics, self.score)]
+	fn try_score(
+		&mut self,
+		physics: &mut Physics,
+		score: &mut usize,
+		points: &str,
+	) -> Result<(), String> {
+		if physics.steps == 0 {
+			return Err("nothing happened yet".to_string());
+		}
+		*score += points.parse::<usize>().map_err(|error| error.to_string())?;
+		self.frame += 1;
+		Ok(())
+	}
+
+	#[with_stolen(self.score)]
+	fn fail(&mut self, score: &mut usize) {
+		*score += 1;
+		panic!("the game crashed");
+	}
+}
+
+#[test]
+fn steals_for_the_whole_body() {
+	let mut game = Game::new();
+	assert_eq!(game.step(2), 2);
+	assert_eq!(game.step(1), 5);
+	assert_eq!(game.physics, Physics { steps: 5 });
+	assert_eq!(game.frame, 3);
+}
+
+#[test]
+fn returns_on_early_returns() {
+	let mut game = Game::new();
+	assert_eq!(game.try_score("1"), Err("nothing happened yet".to_string()));
+	assert!(!game.physics.is_stolen());
+	assert!(!game.score.is_stolen());
+
+	game.step(1);
+	assert!(game.try_score("lots").is_err());
+	assert!(!game.physics.is_stolen());
+	assert!(!game.score.is_stolen());
+
+	assert_eq!(game.try_score("10"), Ok(()));
+	assert_eq!(game.score, 10);
+	assert_eq!(game.frame, 2);
+}
+
+#[test]
+#[cfg(not(feature = "no_std"))]
+fn returns_when_panicking() {
+	let mut game = Game::new();
+	let hook = std::panic::take_hook();
+	std::panic::set_hook(Box::new(|_| {}));
+	let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| game.fail()));
+	std::panic::set_hook(hook);
+
+	assert!(result.is_err());
+	assert!(!game.score.is_stolen());
+	assert_eq!(game.score, 1);
+}
+
+#[test]
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(feature = "abort_on_loss"))]
+fn returns_earlier_values_when_a_field_is_stolen() {
+	let mut game = Game::new();
+	let score = game.score.steal();
+	let hook = std::panic::take_hook();
+	std::panic::set_hook(Box::new(|_| {}));
+	let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| game.try_score("1")));
+	std::panic::set_hook(hook);
+
+	assert!(result.is_err());
+	assert!(!game.physics.is_stolen());
+	game.score.return_stolen(score);
+}
+
+#[test]
+fn rejects_fields_that_are_not_cells() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui_derive/*.rs");
+}
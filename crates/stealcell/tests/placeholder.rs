@@ -0,0 +1,74 @@
+//! Counting allocations needs a global allocator, so these tests live in
+//! their own binary. The placeholders of std types need std.
+#![cfg(not(feature = "no_std"))]
+
+use std::{
+	alloc::{GlobalAlloc, Layout, System},
+	cell::Cell,
+};
+
+use stealcell::StealCell;
+
+/// Counts the allocations of every thread on its own, as tests run in
+/// parallel.
+struct CountingAllocator;
+
+thread_local! {
+	static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+		// SAFETY: Forwarded as is.
+		unsafe { System.alloc(layout) }
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		// SAFETY: Forwarded as is.
+		unsafe { System.dealloc(ptr, layout) }
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Steals and returns once before counting, as the first steal may allocate
+/// the bookkeeping of features like `poison`.
+fn allocations<T>(stealcell: &mut StealCell<T>, fun: impl FnOnce(&mut StealCell<T>)) -> usize {
+	let stolen = stealcell.steal();
+	stealcell.return_stolen(stolen);
+
+	let before = ALLOCATIONS.with(Cell::get);
+	fun(stealcell);
+	ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn vec_placeholders_do_not_allocate() {
+	let mut stealcell = StealCell::new(vec![1, 2, 3]);
+	let allocations = allocations(&mut stealcell, |stealcell| {
+		let mut stolen = stealcell.steal_replace_placeholder();
+		assert!(stealcell.as_ref().is_empty());
+		stolen.get_mut()[0] = 10;
+		stealcell.return_stolen(stolen);
+	});
+	assert_eq!(allocations, 0);
+	assert_eq!(stealcell, vec![10, 2, 3]);
+}
+
+#[test]
+fn string_placeholders_do_not_allocate() {
+	let mut stealcell = StealCell::new("hero".to_string());
+	let allocations = allocations(&mut stealcell, |stealcell| {
+		let stolen = stealcell.steal_replace_placeholder();
+		assert!(stealcell.as_ref().is_empty());
+		let placeholder = stealcell.return_and_take_placeholder(stolen);
+		assert_eq!(
+			placeholder.map(|placeholder| placeholder.capacity()),
+			Some(0)
+		);
+	});
+	assert_eq!(allocations, 0);
+	assert_eq!(stealcell.as_ref(), "hero");
+}
@@ -0,0 +1,79 @@
+//! The rest of the tests check the formatted messages, so only these run
+//! with the `slim_panics` feature. The `typed_panics` feature takes over the
+//! payload, see the typed_panics tests, and `abort_on_loss` aborts instead.
+#![cfg(all(
+	feature = "slim_panics",
+	not(feature = "typed_panics"),
+	not(feature = "abort_on_loss")
+))]
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use stealcell::{StealCell, StealError};
+
+/// Runs the function expecting it to panic with a fixed message.
+fn panic_message(fun: impl FnOnce()) -> String {
+	let hook = std::panic::take_hook();
+	std::panic::set_hook(Box::new(|_| {}));
+	let payload = catch_unwind(AssertUnwindSafe(fun)).expect_err("expected a panic");
+	std::panic::set_hook(hook);
+	payload
+		.downcast_ref::<&str>()
+		.map(|message| message.to_string())
+		.or_else(|| payload.downcast_ref::<String>().cloned())
+		.unwrap()
+}
+
+struct Secret;
+
+#[test]
+fn stealing_twice_panics_with_a_fixed_message() {
+	let mut stealcell = StealCell::new_named(Secret, "vault");
+	let stolen = stealcell.steal();
+	assert_eq!(
+		panic_message(|| drop(stealcell.steal())),
+		"stealcell: value already stolen"
+	);
+	assert_eq!(
+		panic_message(|| drop(stealcell.expect_steal("opening the vault"))),
+		"stealcell: value already stolen"
+	);
+	assert_eq!(
+		panic_message(|| {
+			let _ = stealcell.as_ref();
+		}),
+		"stealcell: value already stolen"
+	);
+	stealcell.return_stolen(stolen);
+}
+
+#[test]
+fn losing_a_value_panics_with_a_fixed_message() {
+	let mut stealcell = StealCell::new(Secret);
+	assert_eq!(
+		panic_message(|| drop(stealcell.steal())),
+		"stealcell: stolen value lost without returning it"
+	);
+}
+
+#[test]
+fn bad_returns_panic_with_a_fixed_message() {
+	let mut stealcell = StealCell::new(Secret);
+	let stolen = stealcell.steal();
+	stealcell.set(Secret).ok().unwrap();
+	assert_eq!(
+		panic_message(|| stealcell.return_stolen(stolen)),
+		"stealcell: stolen value returned to an occupied cell"
+	);
+}
+
+#[test]
+fn errors_have_no_type_names() {
+	let mut stealcell = StealCell::new(Secret);
+	let stolen = stealcell.steal();
+	let Err(StealError::AlreadyStolen { type_name, .. }) = stealcell.try_steal() else {
+		panic!("expected the value to be stolen");
+	};
+	assert_eq!(type_name, "");
+	stealcell.return_stolen(stolen);
+}
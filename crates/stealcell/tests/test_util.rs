@@ -0,0 +1,122 @@
+//! The registry is process wide, so these tests live in their own binary,
+//! and take turns so that they don't see each other's steals.
+#![cfg(feature = "test_util")]
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use stealcell::{StealCell, Stolen, assert_all_returned, outstanding_steals};
+
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+	SERIAL.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn panic_message(fun: impl FnOnce()) -> String {
+	let payload =
+		std::panic::catch_unwind(std::panic::AssertUnwindSafe(fun)).expect_err("expected a panic");
+	payload.downcast_ref::<String>().cloned().unwrap()
+}
+
+#[test]
+fn passes_when_everything_was_returned() {
+	let _serial = serial();
+	let mut stealcell = StealCell::<usize>::tracked(1);
+	let untracked = StealCell::<usize>::new(2).steal();
+	assert_all_returned();
+
+	let stolen = stealcell.steal();
+	stealcell.return_stolen(stolen);
+	let stolen = stealcell.steal();
+	stolen.discard();
+	assert_all_returned();
+	untracked.discard();
+}
+
+#[test]
+fn lists_an_outstanding_value() {
+	let _serial = serial();
+	let mut stealcell = StealCell::<usize>::tracked(1);
+	stealcell.set_label("score");
+	let stolen = stealcell.steal();
+
+	let message = panic_message(assert_all_returned);
+	assert!(
+		message.starts_with("1 stolen values were not returned:\n  usize stolen from 'score' at")
+	);
+	assert!(message.contains(file!()));
+
+	stealcell.return_stolen(stolen);
+	assert_all_returned();
+}
+
+/// Values can only be returned on the thread they were stolen on with
+/// `debug_thread_affinity`.
+#[test]
+#[cfg(not(feature = "debug_thread_affinity"))]
+fn lists_outstanding_values_of_every_thread() {
+	let _serial = serial();
+	let mut cells = [
+		StealCell::<usize>::tracked(1),
+		StealCell::<usize>::tracked(2),
+		StealCell::<usize>::tracked(3),
+	];
+	let stolen: Vec<_> = std::thread::scope(|scope| {
+		let handles: Vec<_> = cells
+			.iter_mut()
+			.map(|stealcell| scope.spawn(|| stealcell.steal()))
+			.collect();
+		handles.into_iter().map(|h| h.join().unwrap()).collect()
+	});
+
+	assert_eq!(outstanding_steals().len(), 3);
+	let message = panic_message(assert_all_returned);
+	assert!(message.starts_with("3 stolen values were not returned:"));
+	assert_eq!(message.matches("\n  usize stolen at").count(), 3);
+
+	for (stealcell, stolen) in cells.iter_mut().zip(stolen) {
+		stealcell.return_stolen(stolen);
+	}
+	assert_all_returned();
+}
+
+#[test]
+fn lists_mapped_values() {
+	let _serial = serial();
+	let mut stealcell = StealCell::<usize>::tracked(1);
+	let mapped = stealcell.steal().map(|value| value + 1);
+	assert_eq!(outstanding_steals().len(), 1);
+	stealcell.return_stolen(mapped);
+	assert_all_returned();
+	assert_eq!(stealcell, 2);
+}
+
+#[test]
+fn lists_split_values() {
+	let _serial = serial();
+	let mut stealcell = StealCell::<(usize, usize)>::tracked((1, 2));
+	let (a, b) = stealcell.steal().split();
+	assert_eq!(outstanding_steals().len(), 1);
+	let zipped = Stolen::zip(a, b);
+	assert_eq!(outstanding_steals().len(), 1);
+	stealcell.return_stolen(zipped);
+	assert_all_returned();
+
+	let mut a = StealCell::<usize>::tracked(3);
+	let mut b = StealCell::<usize>::tracked(4);
+	let zipped = Stolen::zip(a.steal(), b.steal());
+	assert_eq!(outstanding_steals().len(), 1);
+	zipped.discard();
+	assert_all_returned();
+}
+
+#[test]
+fn dropped_cells_unregister() {
+	let _serial = serial();
+	let stolen = {
+		let mut stealcell = StealCell::<usize>::tracked(1);
+		stealcell.steal()
+	};
+	assert_all_returned();
+	stolen.discard();
+}
@@ -0,0 +1,118 @@
+//! The rest of the tests check the formatted messages, so only these run
+//! with the `typed_panics` feature, unless `abort_on_loss` aborts instead.
+#![cfg(all(feature = "typed_panics", not(feature = "abort_on_loss")))]
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use stealcell::{
+	ConsumeCell, DepositCell, StealCell, StealViolation, ViolationKind, sync::AtomicStealCell,
+};
+
+/// The type name in violations, empty with the `slim_panics` feature.
+const USIZE: &str = if cfg!(feature = "slim_panics") {
+	""
+} else {
+	"usize"
+};
+
+/// Whether cells keep their labels and where they were stolen.
+const DIAGNOSTICS: bool = cfg!(any(debug_assertions, feature = "diagnostics"));
+
+/// Runs the function expecting it to panic with a violation.
+fn violation(fun: impl FnOnce()) -> StealViolation {
+	let hook = std::panic::take_hook();
+	std::panic::set_hook(Box::new(|_| {}));
+	let payload = catch_unwind(AssertUnwindSafe(fun)).expect_err("expected a panic");
+	std::panic::set_hook(hook);
+	*payload
+		.downcast::<StealViolation>()
+		.expect("expected a StealViolation payload")
+}
+
+#[test]
+fn stealing_twice_panics_with_a_violation() {
+	let mut stealcell = StealCell::<usize>::new_named(1, "score");
+	let stolen = stealcell.steal();
+
+	for violation in [
+		violation(|| drop(stealcell.steal())),
+		violation(|| drop(stealcell.expect_steal("scoring"))),
+		violation(|| {
+			let _ = stealcell.as_ref();
+		}),
+	] {
+		assert_eq!(violation.kind, ViolationKind::AlreadyStolen);
+		assert_eq!(violation.type_name, USIZE);
+		assert_eq!(violation.label, DIAGNOSTICS.then_some("score"));
+		assert_eq!(
+			violation.stolen_at.map(|location| location.file()),
+			DIAGNOSTICS.then_some(file!())
+		);
+	}
+	stealcell.return_stolen(stolen);
+}
+
+#[test]
+fn losing_a_value_panics_with_a_violation() {
+	let mut stealcell = StealCell::<usize>::new_named(1, "score");
+	let violation = violation(|| drop(stealcell.steal()));
+	assert_eq!(violation.kind, ViolationKind::Lost);
+	assert_eq!(violation.type_name, USIZE);
+	assert_eq!(violation.label, DIAGNOSTICS.then_some("score"));
+	assert!(violation.stolen_at.is_some());
+}
+
+#[test]
+fn bad_returns_panic_with_a_violation() {
+	let mut stealcell = StealCell::<usize>::new(1);
+	let stolen = stealcell.steal();
+	stealcell.set(2).unwrap();
+	let occupied = violation(|| stealcell.return_stolen(stolen));
+	assert_eq!(occupied.kind, ViolationKind::CellOccupied);
+	assert_eq!(occupied.type_name, USIZE);
+	assert_eq!(occupied.label, None);
+
+	// Without diagnostics values can be returned to any cell
+	if DIAGNOSTICS {
+		let stolen = stealcell.steal();
+		let mut other = StealCell::<usize>::empty();
+		let wrong_cell = violation(|| other.return_stolen(stolen));
+		assert_eq!(wrong_cell.kind, ViolationKind::WrongCell);
+
+		stealcell.set(3).unwrap();
+		let stolen = stealcell.steal();
+		stealcell.set(4).unwrap();
+		stealcell.take();
+		let stale = violation(|| stealcell.return_stolen(stolen));
+		assert_eq!(stale.kind, ViolationKind::Stale);
+		assert!(stale.stolen_at.is_some());
+	}
+}
+
+#[test]
+fn other_cells_panic_with_a_violation() {
+	let mut token = ConsumeCell::new_named(1_u8, "wakeup");
+	token.consume();
+	let consumed = violation(|| {
+		token.consume();
+	});
+	assert_eq!(consumed.kind, ViolationKind::AlreadyStolen);
+	assert_eq!(consumed.label, Some("wakeup"));
+
+	let mut a = DepositCell::new();
+	let mut b = DepositCell::new();
+	let receipt = a.deposit(1_usize);
+	let receipt_b = b.deposit(2);
+	let wrong_cell = violation(|| {
+		let _ = b.withdraw(receipt);
+	});
+	assert_eq!(wrong_cell.kind, ViolationKind::WrongCell);
+	assert_eq!(wrong_cell.type_name, USIZE);
+	assert_eq!(b.withdraw(receipt_b), 2);
+
+	let cell = AtomicStealCell::new(1_usize);
+	let stolen = cell.steal();
+	cell.try_insert(2).unwrap();
+	let occupied = violation(|| cell.return_stolen(stolen));
+	assert_eq!(occupied.kind, ViolationKind::CellOccupied);
+}
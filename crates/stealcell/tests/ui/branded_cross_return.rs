@@ -0,0 +1,14 @@
+use stealcell::StealCell;
+
+fn main() {
+	let mut a = StealCell::new(1);
+	let mut b = StealCell::new(2);
+	a.with_brand(|mut a| {
+		b.with_brand(|mut b| {
+			let stolen_a = a.steal();
+			let stolen_b = b.steal();
+			b.return_stolen(stolen_a);
+			a.return_stolen(stolen_b);
+		});
+	});
+}
@@ -0,0 +1,7 @@
+use stealcell::StealCell;
+
+fn main() {
+	let mut stealcell = StealCell::new(1);
+	let stolen = stealcell.with_brand(|mut cell| cell.steal());
+	stolen.into_stolen().discard();
+}
@@ -0,0 +1,6 @@
+use stealcell::StealCell;
+
+fn main() {
+	let mut stealcell = StealCell::new(1);
+	stealcell.lend(|stolen| stolen.into_inner() + 1);
+}
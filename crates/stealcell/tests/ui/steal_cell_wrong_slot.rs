@@ -0,0 +1,9 @@
+stealcell::steal_cell!(PlayerSlot: u32);
+stealcell::steal_cell!(BenchSlot: u32);
+
+fn main() {
+	let mut player_slot = PlayerSlot::new(1);
+	let mut bench_slot = BenchSlot::new(2);
+	let stolen = player_slot.steal();
+	bench_slot.return_stolen(stolen);
+}
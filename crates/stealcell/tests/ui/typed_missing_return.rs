@@ -0,0 +1,14 @@
+use stealcell::typed::StealCell;
+
+struct World {
+	physics: StealCell<u32>,
+}
+
+fn main() {
+	let world = World {
+		physics: StealCell::new(1),
+	};
+	let (stolen, physics) = world.physics.steal();
+	stolen.discard();
+	let _world = World { physics };
+}
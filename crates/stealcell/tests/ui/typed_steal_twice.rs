@@ -0,0 +1,7 @@
+use stealcell::typed::StealCell;
+
+fn main() {
+	let (stolen, empty) = StealCell::new(1).steal();
+	let _ = empty.steal();
+	let _ = empty.return_stolen(stolen);
+}
@@ -0,0 +1,14 @@
+#![deny(unused_must_use)]
+
+use stealcell::{MustReturn, StealCell};
+
+fn proof(stealcell: &mut StealCell<u32>) -> MustReturn {
+	let (stolen, proof) = stealcell.steal_with_proof();
+	stealcell.return_stolen(stolen);
+	proof
+}
+
+fn main() {
+	let mut stealcell = StealCell::new(1);
+	proof(&mut stealcell);
+}
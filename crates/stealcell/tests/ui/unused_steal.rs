@@ -0,0 +1,12 @@
+#![deny(unused_must_use)]
+
+use stealcell::StealCell;
+
+fn main() {
+	let mut stealcell = StealCell::new(1);
+	stealcell.steal();
+	stealcell.try_steal();
+	stealcell.expect_steal("stealcell");
+	stealcell.steal_if(|_| true);
+	stealcell.steal_or_default();
+}
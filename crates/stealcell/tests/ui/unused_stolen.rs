@@ -0,0 +1,9 @@
+#![deny(unused_must_use)]
+
+use stealcell::StealCell;
+
+fn main() {
+	let mut stealcell = StealCell::new(1);
+	let stolen = stealcell.steal();
+	stolen.map(|value| value + 1);
+}
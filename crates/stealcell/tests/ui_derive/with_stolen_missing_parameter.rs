@@ -0,0 +1,14 @@
+use stealcell::{StealCell, with_stolen};
+
+struct Game {
+	score: StealCell<usize>,
+}
+
+impl Game {
+	#[with_stolen(self.score)]
+	fn add(&mut self, points: &mut usize) {
+		*points += 1;
+	}
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use stealcell::with_stolen;
+
+struct Game {
+	score: usize,
+}
+
+impl Game {
+	#[with_stolen(self.score)]
+	fn add(&mut self, score: &mut usize) {
+		*score += 1;
+	}
+}
+
+fn main() {
+	Game { score: 0 }.add();
+}
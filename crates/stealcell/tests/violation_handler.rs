@@ -0,0 +1,87 @@
+//! The handler is installed process wide, so these tests live in their own
+//! binary where it can't interfere with the rest.
+#![cfg(feature = "violation_handler")]
+
+use std::cell::RefCell;
+
+use stealcell::{StealCell, StealViolation, ViolationKind, set_violation_handler};
+
+std::thread_local! {
+	/// Only threads that asked for it record instead of panicking.
+	static RECORDED: RefCell<Option<Vec<StealViolation>>> = const { RefCell::new(None) };
+}
+
+fn recording_handler(violation: &StealViolation) {
+	let recorded = RECORDED.with_borrow_mut(|recorded| {
+		recorded
+			.as_mut()
+			.map(|recorded| recorded.push(*violation))
+			.is_some()
+	});
+	if !recorded {
+		panic!("{violation}");
+	}
+}
+
+fn record(f: impl FnOnce()) -> Vec<ViolationKind> {
+	set_violation_handler(recording_handler);
+	RECORDED.set(Some(Vec::new()));
+	f();
+	let recorded = RECORDED.take().unwrap();
+	recorded.iter().map(|violation| violation.kind).collect()
+}
+
+#[test]
+fn lost_values_are_handed_to_the_handler() {
+	let mut stealcell = StealCell::<usize>::new(1);
+	let recorded = record(|| drop(stealcell.steal()));
+	assert_eq!(recorded, [ViolationKind::Lost]);
+	assert!(stealcell.is_stolen());
+}
+
+#[test]
+fn bad_returns_are_handed_to_the_handler() {
+	let mut a = StealCell::<usize>::new(1);
+	let mut b = StealCell::<usize>::new(2);
+	let recorded = record(|| {
+		let stolen = a.steal();
+		b.return_stolen(stolen);
+		a.return_stolen_unchecked(StealCell::new(3).steal());
+		a.return_stolen_unchecked(StealCell::new(4).steal());
+	});
+	assert_eq!(
+		recorded,
+		[ViolationKind::WrongCell, ViolationKind::CellOccupied]
+	);
+	assert_eq!(a, 3);
+	assert_eq!(b, 2);
+}
+
+/// Stealing twice aborts with `abort_on_loss`, even with a handler.
+#[test]
+#[cfg(not(feature = "abort_on_loss"))]
+fn double_steals_still_panic_after_the_handler() {
+	let mut stealcell = StealCell::<usize>::new(1);
+	let recorded = record(|| {
+		let stolen = stealcell.steal();
+		let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+			let _ = stealcell.steal();
+		}));
+		assert!(result.is_err());
+		stealcell.return_stolen(stolen);
+	});
+	assert_eq!(recorded, [ViolationKind::AlreadyStolen]);
+	assert_eq!(stealcell, 1);
+}
+
+#[test]
+fn rejected_returns_leave_the_value_in_the_cell_alone() {
+	let mut stealcell = StealCell::<usize>::new(1);
+	let recorded = record(|| {
+		let stolen = stealcell.steal();
+		stealcell.set(100).unwrap();
+		stealcell.return_stolen_with(stolen, |value| value * 1000);
+	});
+	assert_eq!(recorded, [ViolationKind::CellOccupied]);
+	assert_eq!(stealcell, 100);
+}
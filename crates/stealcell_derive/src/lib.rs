@@ -0,0 +1,54 @@
+//! Derive and attribute macros for [stealcell](https://docs.rs/stealcell),
+//! re-exported by it with the `derive` feature.
+
+mod placeholder;
+mod stealable;
+mod with_stolen;
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, Expr, ItemFn, Token, parse_macro_input, punctuated::Punctuated};
+
+/// For every `StealCell` field of a struct with named fields, generates a
+/// `steal_<field>` method stealing its value, along with a view giving
+/// `&mut` access to every other field, and a `return_<field>` function
+/// consuming both to return the value.
+///
+/// For a field `thing` of a struct `World`, the view is `WorldWithoutThing`.
+/// The methods and the view fields have the visibility of the fields, the
+/// view has the visibility of the struct.
+#[proc_macro_derive(Stealable)]
+pub fn derive_stealable(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	stealable::expand(input)
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
+/// Implements `StealPlaceholder` for a struct, using the placeholders of
+/// its fields.
+#[proc_macro_derive(StealPlaceholder)]
+pub fn derive_steal_placeholder(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	placeholder::expand(input)
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
+/// Steals from the given fields for the whole body of a method, binding
+/// their values to the parameters named like the fields, which are removed
+/// from the signature. The values are returned on every exit, even on early
+/// returns, `?` and panics, in which case the panic carries on once they are
+/// back.
+///
+/// Used like `#[with_stolen(self.thing)] fn update(&mut self, thing: &mut
+/// Thing)`, which is then called as `world.update()`.
+///
+/// With `no_std`, a panicking body loses the values.
+#[proc_macro_attribute]
+pub fn with_stolen(attribute: TokenStream, item: TokenStream) -> TokenStream {
+	let cells = parse_macro_input!(attribute with Punctuated::<Expr, Token![,]>::parse_terminated);
+	let function = parse_macro_input!(item as ItemFn);
+	with_stolen::expand(cells, function)
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
@@ -0,0 +1,47 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Result, parse_quote};
+
+pub(crate) fn expand(mut input: DeriveInput) -> Result<TokenStream> {
+	let Data::Struct(data) = &input.data else {
+		return Err(Error::new_spanned(
+			&input.ident,
+			"StealPlaceholder can only be derived for structs",
+		));
+	};
+
+	let placeholder = quote! { ::stealcell::StealPlaceholder::placeholder() };
+	let body = match &data.fields {
+		Fields::Named(fields) => {
+			let names = fields.named.iter().map(|field| &field.ident);
+			quote! { Self { #(#names: #placeholder,)* } }
+		}
+		Fields::Unnamed(fields) => {
+			let placeholders = fields.unnamed.iter().map(|_| &placeholder);
+			quote! { Self(#(#placeholders,)*) }
+		}
+		Fields::Unit => quote! { Self },
+	};
+
+	let types = data
+		.fields
+		.iter()
+		.map(|field| field.ty.clone())
+		.collect::<Vec<_>>();
+	let where_clause = input.generics.make_where_clause();
+	for ty in types {
+		where_clause
+			.predicates
+			.push(parse_quote! { #ty: ::stealcell::StealPlaceholder });
+	}
+
+	let name = &input.ident;
+	let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+	Ok(quote! {
+		impl #impl_generics ::stealcell::StealPlaceholder for #name #type_generics #where_clause {
+			fn placeholder() -> Self {
+				#body
+			}
+		}
+	})
+}
@@ -0,0 +1,142 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+	Data, DeriveInput, Error, Fields, GenericArgument, GenericParam, Generics, Lifetime,
+	LifetimeParam, PathArguments, Result, Type,
+};
+
+/// The type of the value in a field of type `StealCell<T>`, if it is one.
+pub(crate) fn stolen_type(ty: &Type) -> Option<&Type> {
+	let Type::Path(path) = ty else {
+		return None;
+	};
+	let segment = path.path.segments.last()?;
+	if segment.ident != "StealCell" {
+		return None;
+	}
+	let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+		return None;
+	};
+	match arguments.args.first()? {
+		GenericArgument::Type(ty) if arguments.args.len() == 1 => Some(ty),
+		_ => None,
+	}
+}
+
+/// `thing_box` to `ThingBox`.
+fn pascal_case(snake_case: &str) -> String {
+	snake_case
+		.split('_')
+		.map(|word| {
+			let mut chars = word.chars();
+			chars
+				.next()
+				.map(|first| first.to_uppercase().chain(chars).collect::<String>())
+				.unwrap_or_default()
+		})
+		.collect()
+}
+
+/// The generics of the struct, with a lifetime in front for its views.
+fn with_lifetime(generics: &Generics, lifetime: Lifetime) -> Generics {
+	let mut generics = generics.clone();
+	generics
+		.params
+		.insert(0, GenericParam::Lifetime(LifetimeParam::new(lifetime)));
+	generics
+}
+
+pub(crate) fn expand(input: DeriveInput) -> Result<TokenStream> {
+	let Data::Struct(data) = &input.data else {
+		return Err(Error::new_spanned(
+			&input.ident,
+			"Stealable can only be derived for structs",
+		));
+	};
+	let Fields::Named(fields) = &data.fields else {
+		return Err(Error::new_spanned(
+			&input.ident,
+			"Stealable can only be derived for structs with named fields",
+		));
+	};
+
+	let name = &input.ident;
+	let vis = &input.vis;
+	let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+	let lifetime = Lifetime::new("'__stealcell", Span::call_site());
+	let view_generics = with_lifetime(&input.generics, lifetime.clone());
+	let elided_generics = with_lifetime(&input.generics, Lifetime::new("'_", Span::call_site()));
+	let (_, elided_type_generics, _) = elided_generics.split_for_impl();
+
+	let mut expanded = TokenStream::new();
+	for field in &fields.named {
+		let Some(stolen) = stolen_type(&field.ty) else {
+			continue;
+		};
+		let field_name = field.ident.as_ref().expect("named fields have names");
+		let field_vis = &field.vis;
+		let view = format_ident!(
+			"{name}Without{}",
+			pascal_case(field_name.to_string().trim_start_matches("r#"))
+		);
+		let steal = format_ident!("steal_{field_name}");
+		let return_stolen = format_ident!("return_{field_name}");
+
+		let rest = fields
+			.named
+			.iter()
+			.filter(|other| other.ident != field.ident)
+			.collect::<Vec<_>>();
+		let rest_names = rest.iter().map(|other| &other.ident).collect::<Vec<_>>();
+		let rest_fields = rest.iter().map(|other| {
+			let (vis, ident, ty) = (&other.vis, &other.ident, &other.ty);
+			quote! { #vis #ident: &#lifetime mut #ty }
+		});
+
+		let view_doc = format!(
+			"`&mut` access to every field of [{name}] except `{field_name}`, whose value is \
+			 stolen, see [{name}::{steal}]."
+		);
+		let steal_doc = format!(
+			"Steals the value of `{field_name}`, along with a view of every other field.\n\n\
+			 Panics if it's already stolen!"
+		);
+		let return_doc = format!(
+			"Returns the value stolen by [{name}::{steal}], ending the view.\n\n\
+			 Panics if it can't be returned, like when `{field_name}` was refilled!"
+		);
+
+		expanded.extend(quote! {
+			// Not every struct steals every field, nor uses every other one
+			// while it does, which shouldn't warn.
+			#[doc = #view_doc]
+			#[allow(dead_code)]
+			#vis struct #view #view_generics #where_clause {
+				#(#rest_fields,)*
+				__stealcell: &#lifetime mut ::stealcell::StealCell<#stolen>,
+			}
+
+			#[allow(dead_code)]
+			impl #impl_generics #name #type_generics #where_clause {
+				#[doc = #steal_doc]
+				#[track_caller]
+				#field_vis fn #steal(&mut self) -> (::stealcell::Stolen<#stolen>, #view #elided_type_generics) {
+					let stolen = self.#field_name.steal();
+					let view = #view {
+						#(#rest_names: &mut self.#rest_names,)*
+						__stealcell: &mut self.#field_name,
+					};
+					(stolen, view)
+				}
+
+				#[doc = #return_doc]
+				#[track_caller]
+				#field_vis fn #return_stolen(stolen: ::stealcell::Stolen<#stolen>, view: #view #elided_type_generics) {
+					view.__stealcell.return_stolen(stolen);
+				}
+			}
+		});
+	}
+	Ok(expanded)
+}
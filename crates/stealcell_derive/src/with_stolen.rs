@@ -0,0 +1,142 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+	Error, Expr, FnArg, Ident, ItemFn, Member, Pat, Result, ReturnType, Token, Type,
+	punctuated::Punctuated, spanned::Spanned,
+};
+
+/// A field to steal from, and the parameter its value is bound to.
+struct Steal {
+	cell: Expr,
+	pat: Pat,
+	ty: Type,
+	stolen: syn::Ident,
+}
+
+pub(crate) fn expand(
+	cells: Punctuated<Expr, Token![,]>,
+	mut function: ItemFn,
+) -> Result<TokenStream> {
+	if let Some(asyncness) = function.sig.asyncness {
+		return Err(Error::new_spanned(
+			asyncness,
+			"#[with_stolen] can't be used on async functions",
+		));
+	}
+	if cells.is_empty() {
+		return Err(Error::new(
+			Span::call_site(),
+			"expected the fields to steal from, like `#[with_stolen(self.thing)]`",
+		));
+	}
+
+	let mut steals = Vec::new();
+	for cell in cells {
+		let Expr::Field(field) = &cell else {
+			return Err(Error::new_spanned(
+				cell,
+				"expected a field to steal from, like `self.thing`",
+			));
+		};
+		let Member::Named(name) = &field.member else {
+			return Err(Error::new_spanned(
+				cell,
+				"expected a named field to steal from, like `self.thing`",
+			));
+		};
+		let name = name.clone();
+
+		let inputs = core::mem::take(&mut function.sig.inputs);
+		let mut parameter = None;
+		for input in inputs {
+			match input {
+				FnArg::Typed(typed)
+					if parameter.is_none()
+						&& matches!(&*typed.pat, Pat::Ident(pat) if pat.ident == name) =>
+				{
+					parameter = Some(typed);
+				}
+				input => function.sig.inputs.push(input),
+			}
+		}
+		let Some(parameter) = parameter else {
+			return Err(Error::new_spanned(
+				&function.sig,
+				format!("expected a `{name}: &mut T` parameter to bind the stolen value to"),
+			));
+		};
+		let Type::Reference(reference) = &*parameter.ty else {
+			return Err(Error::new_spanned(
+				&parameter.ty,
+				"expected a `&mut T` parameter to bind the stolen value to",
+			));
+		};
+		if reference.mutability.is_none() || reference.lifetime.is_some() {
+			return Err(Error::new_spanned(
+				&parameter.ty,
+				"expected a `&mut T` parameter to bind the stolen value to",
+			));
+		}
+
+		steals.push(Steal {
+			cell,
+			pat: *parameter.pat,
+			ty: *parameter.ty,
+			stolen: format_ident!("stolen_{name}", span = Span::mixed_site()),
+		});
+	}
+
+	// Closures can't return `impl Trait`, it's left to inference then.
+	let output = match &function.sig.output {
+		ReturnType::Default => quote! { -> () },
+		ReturnType::Type(_, ty) if matches!(**ty, Type::ImplTrait(_)) => quote! {},
+		ReturnType::Type(arrow, ty) => quote! { #arrow #ty },
+	};
+	let caught = Ident::new("caught", Span::mixed_site());
+	let error = Ident::new("error", Span::mixed_site());
+	// Values stolen before a cell that is already stolen are returned first.
+	let take = steals
+		.iter()
+		.enumerate()
+		.map(|(index, Steal { cell, stolen, .. })| {
+			let roll_back = steals[..index].iter().map(|Steal { cell, stolen, .. }| {
+				quote_spanned! {cell.span()=>
+					::stealcell::__StealCellField::__return(&mut #cell, #stolen);
+				}
+			});
+			quote_spanned! {cell.span()=>
+				let mut #stolen = match ::stealcell::__StealCellField::__try_steal(&mut #cell) {
+					::core::result::Result::Ok(#stolen) => #stolen,
+					::core::result::Result::Err(#error) => {
+						#(#roll_back)*
+						::stealcell::__StealCellField::__fail_steal(&#cell, #error)
+					}
+				};
+			}
+		});
+	let bind = steals.iter().map(
+		|Steal {
+		     pat, ty, stolen, ..
+		 }| {
+			quote! { let #pat: #ty = #stolen.get_mut(); }
+		},
+	);
+	let give_back = steals.iter().map(|Steal { cell, stolen, .. }| {
+		quote_spanned! {cell.span()=>
+			let #caught = ::stealcell::__return_after(#caught, || {
+				::stealcell::__StealCellField::__return(&mut #cell, #stolen);
+			});
+		}
+	});
+	let body = &function.block;
+	function.block = syn::parse_quote! {{
+		#(#take)*
+		let #caught = ::stealcell::__catch_body(|| #output {
+			#(#bind)*
+			#body
+		});
+		#(#give_back)*
+		::stealcell::__resume_body(#caught)
+	}};
+	Ok(quote! { #function })
+}